@@ -8,7 +8,7 @@
 #![allow(clippy::neg_multiply)]
 #![allow(dead_code)]
 use itertools::Itertools;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
 use std::collections::BinaryHeap;
 use std::time::Instant;
 
@@ -46,37 +46,133 @@ impl TimeKeeper {
             elapsed_time >= self.time_threshold
         }
     }
+    #[inline]
+    fn progress(&self) -> f64 {
+        let elapsed_time = self.start_time.elapsed().as_nanos() as f64 * 1e-9;
+        #[cfg(feature = "local")]
+        {
+            elapsed_time * 0.85 / self.time_threshold
+        }
+        #[cfg(not(feature = "local"))]
+        {
+            elapsed_time / self.time_threshold
+        }
+    }
+}
+
+// limits of one search run, the search stops at whichever is reached first
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct SearchBudget {
+    time_threshold: Option<f64>, // s
+    nodes: Option<usize>,
+    iterations: Option<usize>,
+}
+
+impl SearchBudget {
+    fn time(time_threshold: f64) -> Self {
+        SearchBudget {
+            time_threshold: Some(time_threshold),
+            ..Default::default()
+        }
+    }
+    fn nodes(nodes: usize) -> Self {
+        SearchBudget {
+            nodes: Some(nodes),
+            ..Default::default()
+        }
+    }
+    fn iterations(iterations: usize) -> Self {
+        SearchBudget {
+            iterations: Some(iterations),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BudgetKeeper {
+    budget: SearchBudget,
+    time_keeper: TimeKeeper,
+    nodes: usize,
+    iterations: usize,
+}
+
+impl BudgetKeeper {
+    fn new(budget: SearchBudget) -> Self {
+        assert!(
+            budget.time_threshold.is_some() || budget.nodes.is_some() || budget.iterations.is_some(),
+            "search budget has no limit"
+        );
+        BudgetKeeper {
+            budget,
+            time_keeper: TimeKeeper::new(budget.time_threshold.unwrap_or(f64::INFINITY)),
+            nodes: 0,
+            iterations: 0,
+        }
+    }
+    #[inline]
+    fn addNodes(&mut self, nodes: usize) {
+        self.nodes += nodes;
+    }
+    #[inline]
+    fn addIteration(&mut self) {
+        self.iterations += 1;
+    }
+    #[inline]
+    fn isOver(&self) -> bool {
+        matches!(self.budget.nodes, Some(limit) if self.nodes >= limit)
+            || matches!(self.budget.iterations, Some(limit) if self.iterations >= limit)
+            || (self.budget.time_threshold.is_some() && self.time_keeper.isTimeOver())
+    }
+    // used fraction of the tightest limit, in [0, 1]
+    fn progress(&self) -> f64 {
+        let mut progress: f64 = 0.0;
+        if let Some(limit) = self.budget.nodes {
+            progress = progress.max(self.nodes as f64 / limit as f64);
+        }
+        if let Some(limit) = self.budget.iterations {
+            progress = progress.max(self.iterations as f64 / limit as f64);
+        }
+        if self.budget.time_threshold.is_some() {
+            progress = progress.max(self.time_keeper.progress());
+        }
+        progress.min(1.0)
+    }
 }
 
 mod rnd_constructor {
-    use rand::Rng;
-    static mut S: usize = 0;
+    use std::cell::Cell;
     static MAX: usize = 1e9 as usize;
 
+    thread_local! {
+        // one stream per thread, so games played on different threads never share it
+        static S: Cell<usize> = const { Cell::new(0) };
+    }
+
     #[inline]
     pub fn init(seed: usize) {
-        unsafe {
-            if seed == 0 {
-                let t = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs() as usize;
-                S = t
-            } else {
-                S = seed;
-            }
-        }
+        let seed = if seed == 0 {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as usize
+        } else {
+            seed
+        };
+        S.with(|s| s.set(seed));
     }
     #[inline]
     pub fn gen() -> usize {
-        unsafe {
-            if S == 0 {
+        S.with(|s| {
+            if s.get() == 0 {
                 init(0);
             }
-            S ^= S << 7;
-            S ^= S >> 9;
-            S
-        }
+            let mut x = s.get();
+            x ^= x << 7;
+            x ^= x >> 9;
+            s.set(x);
+            x
+        })
     }
     #[inline]
     pub fn gen_range(a: usize, b: usize) -> usize {
@@ -93,34 +189,38 @@ mod rnd_constructor {
 }
 
 mod rnd_action {
-    use rand::Rng;
-    static mut S: usize = 0;
+    use std::cell::Cell;
     static MAX: usize = 1e9 as usize;
 
+    thread_local! {
+        // one stream per thread, so games played on different threads never share it
+        static S: Cell<usize> = const { Cell::new(0) };
+    }
+
     #[inline]
     pub fn init(seed: usize) {
-        unsafe {
-            if seed == 0 {
-                let t = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs() as usize;
-                S = t
-            } else {
-                S = seed;
-            }
-        }
+        let seed = if seed == 0 {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as usize
+        } else {
+            seed
+        };
+        S.with(|s| s.set(seed));
     }
     #[inline]
     pub fn gen() -> usize {
-        unsafe {
-            if S == 0 {
+        S.with(|s| {
+            if s.get() == 0 {
                 init(0);
             }
-            S ^= S << 7;
-            S ^= S >> 9;
-            S
-        }
+            let mut x = s.get();
+            x ^= x << 7;
+            x ^= x >> 9;
+            s.set(x);
+            x
+        })
     }
     #[inline]
     pub fn gen_range(a: usize, b: usize) -> usize {
@@ -315,15 +415,20 @@ fn randomAction(state: &AutoMoveMazeState) -> AutoMoveMazeState {
     now_state
 }
 
-fn hillClimb(state: &AutoMoveMazeState, number: usize) -> AutoMoveMazeState {
+// one iteration evaluates one neighbour, which is also counted as one node
+fn hillClimb(state: &AutoMoveMazeState, budget: SearchBudget, stats: &mut SearchStats) -> AutoMoveMazeState {
     let mut now_state = state.clone();
     now_state.init();
     let mut best_score = now_state.getScore(false);
+    let mut budget_keeper = BudgetKeeper::new(budget);
 
-    for _ in 0..number {
+    while !budget_keeper.isOver() {
         let mut next_state = now_state.clone();
         next_state.transition();
         let next_score = next_state.getScore(false);
+        budget_keeper.addNodes(1);
+        budget_keeper.addIteration();
+        stats.nodes_expanded += 1;
         if next_score > best_score {
             best_score = next_score;
             now_state = next_state;
@@ -332,19 +437,30 @@ fn hillClimb(state: &AutoMoveMazeState, number: usize) -> AutoMoveMazeState {
     now_state
 }
 
-fn simulatedAnnealing(state: &AutoMoveMazeState, number: usize, start_temp: f64, end_temp: f64) -> AutoMoveMazeState {
+// the temperature follows the used fraction of the budget
+fn simulatedAnnealing(
+    state: &AutoMoveMazeState,
+    budget: SearchBudget,
+    start_temp: f64,
+    end_temp: f64,
+    stats: &mut SearchStats,
+) -> AutoMoveMazeState {
     let mut now_state = state.clone();
     now_state.init();
     let mut best_score = now_state.getScore(false);
     let mut now_score = best_score;
     let mut best_state = now_state.clone();
+    let mut budget_keeper = BudgetKeeper::new(budget);
 
-    for i in 0..number {
+    while !budget_keeper.isOver() {
         let mut next_state = now_state.clone();
         next_state.transition();
         let next_score = next_state.getScore(false);
 
-        let temp = start_temp + (end_temp - start_temp) * (i as f64 / number as f64);
+        let temp = start_temp + (end_temp - start_temp) * budget_keeper.progress();
+        budget_keeper.addNodes(1);
+        budget_keeper.addIteration();
+        stats.nodes_expanded += 1;
         // next_score >= now_score => next_score - now_score >= 0 => good
         let probability = ((next_score as f64 - now_score as f64) / temp).exp();
         // 0 <= rng.gen::<f64>() <= 1
@@ -360,54 +476,228 @@ fn simulatedAnnealing(state: &AutoMoveMazeState, number: usize, start_temp: f64,
     best_state
 }
 
-type Handler = Box<dyn FnMut(&AutoMoveMazeState, usize) -> AutoMoveMazeState>;
+// summary statistics of a sample, e.g. the scores of a series of games
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Distribution {
+    n: usize,
+    mean: f64,
+    // sample standard deviation, 0 for fewer than two values
+    std_dev: f64,
+    min: f64,
+    p5: f64,
+    median: f64,
+    p95: f64,
+    max: f64,
+    // 95% confidence interval of the mean, normal approximation
+    ci_low: f64,
+    ci_high: f64,
+}
+
+impl Distribution {
+    fn new(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Distribution::default();
+        }
+        let n = values.len();
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        // linear interpolation between the closest ranks
+        let percentile = |p: f64| -> f64 {
+            let rank = p * (n - 1) as f64;
+            let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+            sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+        };
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let std_dev = if n < 2 {
+            0.0
+        } else {
+            (values
+                .iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1) as f64)
+                .sqrt()
+        };
+        let half_width = 1.96 * std_dev / (n as f64).sqrt();
+        Distribution {
+            n,
+            mean,
+            std_dev,
+            min: sorted[0],
+            p5: percentile(0.05),
+            median: percentile(0.5),
+            p95: percentile(0.95),
+            max: sorted[n - 1],
+            ci_low: mean - half_width,
+            ci_high: mean + half_width,
+        }
+    }
 
-fn playGame(ai: &mut (&str, Handler), seed_constructor: usize, simulate_number: usize) {
+    // values scaled by `scale`, e.g. 1e3 for seconds to milliseconds
+    fn scaled(&self, scale: f64) -> Self {
+        Distribution {
+            n: self.n,
+            mean: self.mean * scale,
+            std_dev: self.std_dev * scale,
+            min: self.min * scale,
+            p5: self.p5 * scale,
+            median: self.median * scale,
+            p95: self.p95 * scale,
+            max: self.max * scale,
+            ci_low: self.ci_low * scale,
+            ci_high: self.ci_high * scale,
+        }
+    }
+}
+
+impl std::fmt::Display for Distribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "mean {:.2}, std dev {:.2}, min {:.2}, p5 {:.2}, median {:.2}, p95 {:.2}, max {:.2}, 95% CI [{:.2}, {:.2}]",
+            self.mean, self.std_dev, self.min, self.p5, self.median, self.p95, self.max, self.ci_low, self.ci_high
+        )
+    }
+}
+
+// comparison of two series of games played on the same boards, game i of one against game i of the other
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct PairedComparison {
+    // games where the first series scored higher, the same, lower
+    wins: usize,
+    ties: usize,
+    losses: usize,
+    // first minus second, per game
+    difference: Distribution,
+    // two-sided Wilcoxon signed-rank test, normal approximation with tie and continuity correction
+    p_value: f64,
+}
+
+impl PairedComparison {
+    fn new(first: &[f64], second: &[f64]) -> Self {
+        assert_eq!(
+            first.len(),
+            second.len(),
+            "paired series must have the same length"
+        );
+        let differences: Vec<f64> = first.iter().zip(second).map(|(a, b)| a - b).collect();
+        let mut nonzero: Vec<f64> = differences.iter().copied().filter(|&d| d != 0.0).collect();
+        nonzero.sort_by(|a, b| a.abs().partial_cmp(&b.abs()).unwrap());
+
+        // ranks of |d| starting from 1, tied values share their average rank
+        let n = nonzero.len();
+        let mut rank_sum_positive = 0.0;
+        let mut tie_correction = 0.0;
+        let mut i = 0;
+        while i < n {
+            let mut j = i;
+            while j + 1 < n && nonzero[j + 1].abs() == nonzero[i].abs() {
+                j += 1;
+            }
+            let rank = (i + j + 2) as f64 / 2.0;
+            let tied = (j - i + 1) as f64;
+            tie_correction += tied * tied * tied - tied;
+            rank_sum_positive += rank * nonzero[i..=j].iter().filter(|&&d| d > 0.0).count() as f64;
+            i = j + 1;
+        }
+        let n = n as f64;
+        let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - tie_correction / 48.0;
+        let p_value = if variance <= 0.0 {
+            1.0
+        } else {
+            let z = ((rank_sum_positive - n * (n + 1.0) / 4.0).abs() - 0.5).max(0.0) / variance.sqrt();
+            libm::erfc(z / std::f64::consts::SQRT_2)
+        };
+
+        PairedComparison {
+            wins: differences.iter().filter(|&&d| d > 0.0).count(),
+            ties: differences.iter().filter(|&&d| d == 0.0).count(),
+            losses: differences.iter().filter(|&&d| d < 0.0).count(),
+            difference: Distribution::new(&differences),
+            p_value,
+        }
+    }
+}
+
+impl std::fmt::Display for PairedComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "win/tie/loss {}/{}/{}, difference mean {:.2} 95% CI [{:.2}, {:.2}], Wilcoxon p = {:.4}",
+            self.wins,
+            self.ties,
+            self.losses,
+            self.difference.mean,
+            self.difference.ci_low,
+            self.difference.ci_high,
+            self.p_value
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SearchStats {
+    // states evaluated by getScore during the search
+    nodes_expanded: usize,
+}
+
+// shared by the worker threads of testAiScore, randomness comes from the thread's rnd_action stream
+type Handler = Box<dyn Fn(&AutoMoveMazeState, usize, &mut SearchStats) -> AutoMoveMazeState + Sync>;
+
+fn playGame(ai: &(&str, Handler), seed_constructor: usize, simulate_number: usize) {
     println!("seed constructor: {}", seed_constructor);
     rnd_constructor::init(seed_constructor);
 
     let mut state = AutoMoveMazeState::new();
-    state = ai.1(&state, simulate_number);
+    state = ai.1(&state, simulate_number, &mut SearchStats::default());
     let score = state.getScore(false);
     println!("Score of {}: {}", ai.0, score);
 }
 
-fn single_play(seed_constructor: usize, simulate_number: usize) {
-    let start = Instant::now();
-    playGame(
-        &mut (
+// every AI of single_play and repeat_play
+fn makeAis(start_temp: f64, end_temp: f64) -> Vec<(&'static str, Handler)> {
+    vec![
+        (
             "randomAction",
-            Box::new(|state: &AutoMoveMazeState, _simulate_number: usize| -> AutoMoveMazeState { randomAction(state) }),
+            Box::new(
+                |state: &AutoMoveMazeState, _simulate_number: usize, _stats: &mut SearchStats| -> AutoMoveMazeState {
+                    randomAction(state)
+                },
+            ),
         ),
-        seed_constructor,
-        simulate_number,
-    );
-
-    playGame(
-        &mut (
+        (
             "hillClimb",
             Box::new(
-                |state: &AutoMoveMazeState, simulate_number: usize| -> AutoMoveMazeState {
-                    hillClimb(state, simulate_number)
+                |state: &AutoMoveMazeState, simulate_number: usize, stats: &mut SearchStats| -> AutoMoveMazeState {
+                    hillClimb(state, SearchBudget::iterations(simulate_number), stats)
                 },
             ),
         ),
-        seed_constructor,
-        simulate_number,
-    );
-
-    playGame(
-        &mut (
+        (
             "simulatedAnnealing",
             Box::new(
-                |state: &AutoMoveMazeState, simulate_number: usize| -> AutoMoveMazeState {
-                    simulatedAnnealing(state, simulate_number, 500.0, 10.0)
+                move |state: &AutoMoveMazeState,
+                      simulate_number: usize,
+                      stats: &mut SearchStats|
+                      -> AutoMoveMazeState {
+                    simulatedAnnealing(
+                        state,
+                        SearchBudget::iterations(simulate_number),
+                        start_temp,
+                        end_temp,
+                        stats,
+                    )
                 },
             ),
         ),
-        seed_constructor,
-        simulate_number,
-    );
+    ]
+}
+
+fn single_play(seed_constructor: usize, simulate_number: usize, start_temp: f64, end_temp: f64) {
+    let start = Instant::now();
+    for ai in makeAis(start_temp, end_temp).iter() {
+        playGame(ai, seed_constructor, simulate_number);
+    }
 
     println!(
         "Elapsed time: {}sec",
@@ -415,59 +705,748 @@ fn single_play(seed_constructor: usize, simulate_number: usize) {
     );
 }
 
-fn testAiScore(ai: &mut (&str, Handler), seed_constructor: usize, simulate_number: usize, game_number: usize) {
-    println!("seed constructor: {}", seed_constructor);
-    rnd_constructor::init(seed_constructor);
+// seed of game i of a series started from `seed`, never 0 so that init doesn't fall back to the clock
+fn gameSeed(seed: usize, game: usize) -> usize {
+    let mut z = (seed as u64).wrapping_add((game as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    (z ^ (z >> 31)).max(1) as usize
+}
 
-    let mut score_mean = 0.0;
+// calls play(i) for every game i in 0..game_number on `jobs` worker threads
+// results come back in game order, so they never depend on the thread count or scheduling
+fn runGames<T: Send, F: Fn(usize) -> T + Sync>(game_number: usize, jobs: usize, play: F) -> Vec<T> {
+    let next_game = std::sync::atomic::AtomicUsize::new(0);
+    let mut results: Vec<(usize, T)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.clamp(1, game_number.max(1)))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let game = next_game.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        if game >= game_number {
+                            break;
+                        }
+                        results.push((game, play(game)));
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    results.sort_by_key(|&(game, _)| game);
+    results.into_iter().map(|(_, result)| result).collect()
+}
 
-    for _ in 0..game_number {
+// result of testAiScore for one AI
+#[derive(Debug, Clone)]
+struct ScoreSummary {
+    name: String,
+    // settings the AI was played with, space-separated key=value pairs
+    parameters: String,
+    // board seed of every game, and its action seed, score, play time [s] and evaluated states at the same index
+    seeds: Vec<usize>,
+    action_seeds: Vec<usize>,
+    scores: Vec<f64>,
+    game_times: Vec<f64>,
+    game_nodes: Vec<usize>,
+}
+
+// game i is played on the board of gameSeed(seed_constructor, i), the same board for every AI,
+// with the actions drawn from gameSeed(seed_action, i), on `jobs` worker threads
+fn playGames(
+    ai: &(&str, Handler),
+    seed_constructor: usize,
+    seed_action: usize,
+    simulate_number: usize,
+    games: std::ops::Range<usize>,
+    jobs: usize,
+) -> ScoreSummary {
+    let results = runGames(games.len(), jobs, |game| {
+        let game = games.start + game;
+        let game_start = Instant::now();
+        let seed = gameSeed(seed_constructor, game);
+        let action_seed = gameSeed(seed_action, game);
+        rnd_constructor::init(seed);
+        rnd_action::init(action_seed);
+        let mut stats = SearchStats::default();
         let mut state = AutoMoveMazeState::new();
-        state = ai.1(&state, simulate_number);
-        score_mean += state.getScore(false) as f64;
+        state = ai.1(&state, simulate_number, &mut stats);
+        (
+            seed,
+            action_seed,
+            state.getScore(false) as f64,
+            game_start.elapsed().as_secs_f64(),
+            stats.nodes_expanded,
+        )
+    });
+    ScoreSummary {
+        name: ai.0.to_string(),
+        parameters: String::new(),
+        seeds: results.iter().map(|result| result.0).collect(),
+        action_seeds: results.iter().map(|result| result.1).collect(),
+        scores: results.iter().map(|result| result.2).collect(),
+        game_times: results.iter().map(|result| result.3).collect(),
+        game_nodes: results.iter().map(|result| result.4).collect(),
     }
-    score_mean /= game_number as f64;
-    println!("Score of {}: {}", ai.0, score_mean);
 }
 
-fn repeat_play(seed_constructor: usize, simulate_number: usize) {
-    let game_number = 1000;
-    let mut ais: Vec<(&str, Handler)> = vec![
-        (
-            "randomAction",
-            Box::new(|state: &AutoMoveMazeState, _simulate_number: usize| -> AutoMoveMazeState { randomAction(state) }),
-        ),
-        (
-            "hillClimb",
-            Box::new(
-                |state: &AutoMoveMazeState, simulate_number: usize| -> AutoMoveMazeState {
-                    hillClimb(state, simulate_number)
-                },
-            ),
-        ),
-        (
-            "simulatedAnnealing",
-            Box::new(
-                |state: &AutoMoveMazeState, simulate_number: usize| -> AutoMoveMazeState {
-                    simulatedAnnealing(state, simulate_number, 500.0, 10.0)
-                },
-            ),
-        ),
-    ];
+// playGames of the first game_number games, with their score and time distributions printed
+fn testAiScore(
+    ai: &(&str, Handler),
+    seed_constructor: usize,
+    seed_action: usize,
+    simulate_number: usize,
+    game_number: usize,
+    jobs: usize,
+) -> ScoreSummary {
+    println!("seed constructor: {}", seed_constructor);
 
-    for ai in ais.iter_mut() {
-        let start = Instant::now();
+    let summary = playGames(
+        ai,
+        seed_constructor,
+        seed_action,
+        simulate_number,
+        0..game_number,
+        jobs,
+    );
+    let score = Distribution::new(&summary.scores);
+    println!("Score of {}: {}", ai.0, score.mean);
+    println!("Score distribution: {}", score);
+    println!(
+        "Game time distribution [ms]: {}",
+        Distribution::new(&summary.game_times).scaled(1e3)
+    );
+    summary
+}
+
+// every pair of AIs, game by game on the same boards
+fn printComparisons(summaries: &[ScoreSummary]) {
+    for (i, first) in summaries.iter().enumerate() {
+        for second in &summaries[i + 1..] {
+            assert_eq!(
+                first.seeds, second.seeds,
+                "compared summaries must share their seeds"
+            );
+            println!(
+                "{} vs {}: {}",
+                first.name,
+                second.name,
+                PairedComparison::new(&first.scores, &second.scores)
+            );
+            let differences: Vec<String> = first
+                .seeds
+                .iter()
+                .zip(first.scores.iter().zip(&second.scores))
+                .map(|(seed, (a, b))| format!("{}:{:+}", seed, a - b))
+                .collect();
+            println!(
+                "Per-seed differences (seed:difference): {}",
+                differences.join(" ")
+            );
+        }
+    }
+}
+
+// AIs from best to worst mean score, all played on the same boards
+fn printRanking(summaries: &[ScoreSummary]) {
+    let mut ranked: Vec<(&ScoreSummary, Distribution)> = summaries
+        .iter()
+        .map(|summary| (summary, Distribution::new(&summary.scores)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.mean.partial_cmp(&a.1.mean).unwrap());
+    println!("Ranking over {} games:", summaries[0].seeds.len());
+    println!(
+        "{:>4} {:<20} {:>8} {:>19} {:>8} {:>10}  parameters",
+        "rank", "algorithm", "mean", "95% CI", "std dev", "time [ms]"
+    );
+    for (rank, (summary, score)) in ranked.iter().enumerate() {
+        println!(
+            "{:>4} {:<20} {:>8.2} {:>19} {:>8.2} {:>10.3}  {}",
+            rank + 1,
+            summary.name,
+            score.mean,
+            format!("[{:.2}, {:.2}]", score.ci_low, score.ci_high),
+            score.std_dev,
+            Distribution::new(&summary.game_times).scaled(1e3).mean,
+            summary.parameters
+        );
+    }
+}
+
+// every AI under every (simulate_number, start_temp, end_temp) configuration on the same boards,
+// AIs that ignore the swept settings are played once
+fn repeat_play(
+    seed_constructor: usize,
+    seed_action: usize,
+    configurations: &[(usize, f64, f64)],
+    game_number: usize,
+    jobs: usize,
+) -> Vec<ScoreSummary> {
+    let mut summaries: Vec<ScoreSummary> = vec![];
+    for &(simulate_number, start_temp, end_temp) in configurations {
+        for ai in makeAis(start_temp, end_temp).iter() {
+            let parameters = match ai.0 {
+                "randomAction" => String::new(),
+                "hillClimb" => format!("simulate_number={}", simulate_number),
+                _ => format!(
+                    "simulate_number={} start_temp={} end_temp={}",
+                    simulate_number, start_temp, end_temp
+                ),
+            };
+            if summaries
+                .iter()
+                .any(|summary| summary.name == ai.0 && summary.parameters == parameters)
+            {
+                continue;
+            }
+            let start = Instant::now();
+
+            let mut summary = testAiScore(
+                ai,
+                seed_constructor,
+                seed_action,
+                simulate_number,
+                game_number,
+                jobs,
+            );
+            println!("Parameters: {}", parameters);
+            summary.parameters = parameters;
+            summaries.push(summary);
+
+            println!(
+                "Elapsed time: {}sec",
+                start.elapsed().as_millis() as f64 / 1000.0
+            );
+        }
+    }
+
+    if configurations.len() > 1 {
+        printRanking(&summaries);
+    } else {
+        printComparisons(&summaries);
+    }
+    summaries
+}
+
+// settings of simulatedAnnealing tried by tune, with its scores on the training games played so far
+#[derive(Debug, Clone)]
+struct TuneCandidate {
+    simulate_number: usize,
+    start_temp: f64,
+    end_temp: f64,
+    scores: Vec<f64>,
+}
+
+impl TuneCandidate {
+    fn parameters(&self) -> String {
+        format!(
+            "simulate_number={} start_temp={} end_temp={}",
+            self.simulate_number, self.start_temp, self.end_temp
+        )
+    }
+
+    fn mean(&self) -> f64 {
+        self.scores.iter().sum::<f64>() / self.scores.len() as f64
+    }
+}
+
+// successive halving over the temperatures of `candidates`, all at one simulate_number:
+// every round plays the survivors on twice as many training games as the last and keeps the better half,
+// the last round plays the final two on all training games
+fn halveCandidates(
+    mut candidates: Vec<TuneCandidate>,
+    seed_constructor: usize,
+    seed_action: usize,
+    training_games: usize,
+    jobs: usize,
+) -> TuneCandidate {
+    let halvings = candidates.len().next_power_of_two().trailing_zeros() as usize;
+    for round in 0..halvings {
+        let games = (training_games >> (halvings - 1 - round)).max(1);
+        println!(
+            "Round {}: {} candidates on {} training games",
+            round + 1,
+            candidates.len(),
+            games
+        );
+        for candidate in candidates.iter_mut() {
+            let ai = makeAis(candidate.start_temp, candidate.end_temp)
+                .into_iter()
+                .find(|ai| ai.0 == "simulatedAnnealing")
+                .unwrap();
+            // games played in earlier rounds are kept, they would score the same again
+            let summary = playGames(
+                &ai,
+                seed_constructor,
+                seed_action,
+                candidate.simulate_number,
+                candidate.scores.len()..games,
+                jobs,
+            );
+            candidate.scores.extend(summary.scores);
+        }
+        // stable, so ties keep the hand-picked setting in front
+        candidates.sort_by(|a, b| b.mean().partial_cmp(&a.mean()).unwrap());
+        for candidate in candidates.iter() {
+            println!("{:>8.2}  {}", candidate.mean(), candidate.parameters());
+        }
+        candidates.truncate(candidates.len().div_ceil(2));
+    }
+    candidates.swap_remove(0)
+}
 
-        testAiScore(ai, seed_constructor, simulate_number, game_number);
+// random search over start_temp and end_temp of simulatedAnnealing at every budget tier of simulate_numbers
+// more iterations win regardless of the temperatures, so candidates only compete within their tier,
+// and each tier reports its best temperatures with their held-out score and cost per game
+// the same temperatures are drawn for every tier, the first are the hand-picked ones
+// held-out boards come from another seed series than the training boards, never seen while tuning
+#[allow(clippy::too_many_arguments)]
+fn tune(
+    seed_constructor: usize,
+    seed_action: usize,
+    candidate_number: usize,
+    training_games: usize,
+    held_out_games: usize,
+    simulate_numbers: &[usize],
+    start_temp: f64,
+    end_temp: f64,
+    jobs: usize,
+) -> Vec<ScoreSummary> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed_action as u64);
+    // log-uniform in [low, high], rounded to hundredths
+    let mut logUniform = |low: f64, high: f64| -> f64 {
+        let x = (low.ln() + rng.gen::<f64>() * (high.ln() - low.ln())).exp();
+        ((x * 100.0).round() / 100.0).clamp(low, high)
+    };
+    let mut temperatures = vec![(start_temp, end_temp)];
+    while temperatures.len() < candidate_number {
+        let start_temp = logUniform(1.0, 1000.0);
+        temperatures.push((start_temp, logUniform(0.01, start_temp)));
+    }
 
+    println!("seed constructor: {}", seed_constructor);
+    let mut summaries: Vec<ScoreSummary> = vec![];
+    let mut tiers = vec![];
+    for &simulate_number in simulate_numbers {
+        let start = Instant::now();
+        println!("-----{} iterations-----", simulate_number);
+        let candidates: Vec<TuneCandidate> = temperatures
+            .iter()
+            .map(|&(start_temp, end_temp)| TuneCandidate {
+                simulate_number,
+                start_temp,
+                end_temp,
+                scores: vec![],
+            })
+            .collect();
+        let hand_picked = candidates[0].clone();
+        let best = halveCandidates(
+            candidates,
+            seed_constructor,
+            seed_action,
+            training_games,
+            jobs,
+        );
+        println!(
+            "Best on training games: {:.2}  {}",
+            best.mean(),
+            best.parameters()
+        );
+
+        let mut compared = vec![("tuned", &best)];
+        if hand_picked.parameters() != best.parameters() {
+            compared.push(("hand-picked", &hand_picked));
+        }
+        let mut tier_summaries = vec![];
+        for (label, candidate) in compared {
+            println!(
+                "{} settings on held-out games: {}",
+                label,
+                candidate.parameters()
+            );
+            let ai = makeAis(candidate.start_temp, candidate.end_temp)
+                .into_iter()
+                .find(|ai| ai.0 == "simulatedAnnealing")
+                .unwrap();
+            let mut summary = testAiScore(
+                &ai,
+                !seed_constructor,
+                seed_action,
+                candidate.simulate_number,
+                held_out_games,
+                jobs,
+            );
+            summary.parameters = candidate.parameters();
+            tier_summaries.push(summary);
+        }
+        let held_out = Distribution::new(&tier_summaries[0].scores);
+        println!(
+            "Tuned mean score: {:.2} on training games, {:.2} on held-out games ({:+.2})",
+            best.mean(),
+            held_out.mean,
+            held_out.mean - best.mean()
+        );
+        let gain = match &tier_summaries[..] {
+            [tuned, hand_picked] => {
+                let comparison = PairedComparison::new(&tuned.scores, &hand_picked.scores);
+                println!("tuned vs hand-picked: {}", comparison);
+                comparison.difference.mean
+            }
+            _ => 0.0,
+        };
         println!(
             "Elapsed time: {}sec",
             start.elapsed().as_millis() as f64 / 1000.0
         );
+        let game_time = Distribution::new(&tier_summaries[0].game_times).mean;
+        tiers.push((best, held_out.mean, gain, game_time));
+        summaries.extend(tier_summaries);
+    }
+
+    // the cheapest tier within reach of the best one may be the better buy
+    println!("=====Budget Tiers=====");
+    for (best, held_out_mean, gain, game_time) in tiers.iter() {
+        println!(
+            "{:>8.2} held-out ({:+.2} vs hand-picked), {:>8.3}ms per game  {}",
+            held_out_mean,
+            gain,
+            game_time * 1e3,
+            best.parameters()
+        );
+    }
+    summaries
+}
+
+// one row per game: seed,action_seed,algorithm,parameters,score,elapsed_ms,nodes_expanded
+fn writeGamesCsv(path: &str, summaries: &[ScoreSummary]) -> std::io::Result<()> {
+    let mut text = String::from("seed,action_seed,algorithm,parameters,score,elapsed_ms,nodes_expanded\n");
+    for summary in summaries {
+        for game in 0..summary.seeds.len() {
+            text += &format!(
+                "{},{},{},{},{},{:.3},{}\n",
+                summary.seeds[game],
+                summary.action_seeds[game],
+                summary.name,
+                summary.parameters,
+                summary.scores[game],
+                summary.game_times[game] * 1e3,
+                summary.game_nodes[game]
+            );
+        }
+    }
+    std::fs::write(path, text)
+}
+
+// the rows of writeGamesCsv as an array of objects
+fn writeGamesJson(path: &str, summaries: &[ScoreSummary]) -> std::io::Result<()> {
+    let mut rows = vec![];
+    for summary in summaries {
+        for game in 0..summary.seeds.len() {
+            rows.push(format!(
+                "  {{\"seed\":{},\"action_seed\":{},\"algorithm\":\"{}\",\"parameters\":\"{}\",\"score\":{},\"elapsed_ms\":{:.3},\"nodes_expanded\":{}}}",
+                summary.seeds[game],
+                summary.action_seeds[game],
+                summary.name,
+                summary.parameters,
+                summary.scores[game],
+                summary.game_times[game] * 1e3,
+                summary.game_nodes[game]
+            ));
+        }
+    }
+    std::fs::write(path, format!("[\n{}\n]\n", rows.join(",\n")))
+}
+
+// one game of a file written by writeGamesCsv
+#[derive(Debug, Clone)]
+struct BaselineGame {
+    seed: usize,
+    // scores depend on the actions drawn as well as the board
+    action_seed: usize,
+    algorithm: String,
+    parameters: String,
+    score: f64,
+}
+
+// every game in a file written by writeGamesCsv
+fn readBaseline(path: &str) -> Result<Vec<BaselineGame>, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let mut lines = text.lines().enumerate();
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| format!("{}: empty baseline", path))?;
+    let columns: Vec<&str> = header.split(',').collect();
+    let column = |name: &str| -> Result<usize, String> {
+        columns
+            .iter()
+            .position(|&column| column == name)
+            .ok_or_else(|| format!("{}: no {} column", path, name))
+    };
+    let (seed, action_seed, algorithm, parameters, score) = (
+        column("seed")?,
+        column("action_seed")?,
+        column("algorithm")?,
+        column("parameters")?,
+        column("score")?,
+    );
+    let mut games = vec![];
+    for (i, line) in lines.filter(|(_, line)| !line.is_empty()) {
+        let fields: Vec<&str> = line.split(',').collect();
+        let invalid = || format!("{}: line {}: invalid row", path, i + 1);
+        if fields.len() != columns.len() {
+            return Err(invalid());
+        }
+        games.push(BaselineGame {
+            seed: fields[seed].parse().map_err(|_| invalid())?,
+            action_seed: fields[action_seed].parse().map_err(|_| invalid())?,
+            algorithm: fields[algorithm].to_string(),
+            parameters: fields[parameters].to_string(),
+            score: fields[score].parse().map_err(|_| invalid())?,
+        });
+    }
+    Ok(games)
+}
+
+// compares the mean score of every summary with the baseline games of the same algorithm, parameters,
+// board and action seeds
+// returns false if any mean dropped by more than `tolerance` points or has no baseline to compare with
+fn checkBaseline(summaries: &[ScoreSummary], baseline: &[BaselineGame], tolerance: f64) -> bool {
+    let mut passed = true;
+    for summary in summaries {
+        let mut score_sum = 0.0;
+        let mut baseline_sum = 0.0;
+        let mut games = 0;
+        for (game, &seed) in summary.seeds.iter().enumerate() {
+            if let Some(baseline_game) = baseline.iter().find(|baseline_game| {
+                baseline_game.seed == seed
+                    && baseline_game.action_seed == summary.action_seeds[game]
+                    && baseline_game.algorithm == summary.name
+                    && baseline_game.parameters == summary.parameters
+            }) {
+                score_sum += summary.scores[game];
+                baseline_sum += baseline_game.score;
+                games += 1;
+            }
+        }
+        if games == 0 {
+            println!(
+                "Baseline check of {} ({}): FAILED, no baseline games with these parameters and seeds",
+                summary.name, summary.parameters
+            );
+            passed = false;
+            continue;
+        }
+        let (score_mean, baseline_mean) = (score_sum / games as f64, baseline_sum / games as f64);
+        let ok = score_mean >= baseline_mean - tolerance;
+        println!(
+            "Baseline check of {} ({}): mean {:.2} vs baseline {:.2} over {} games (tolerance {:.2}): {}",
+            summary.name,
+            summary.parameters,
+            score_mean,
+            baseline_mean,
+            games,
+            tolerance,
+            if ok { "ok" } else { "FAILED" }
+        );
+        passed &= ok;
+    }
+    passed
+}
+
+const USAGE: &str = "usage: auto_move_maze [options]
+       auto_move_maze check-baseline BASELINE [options]
+       auto_move_maze tune [options]
+  -n, --games N            games per AI in repeat play, held-out games of tune (default 1000)
+      --simulate-number N  iterations of hillClimb and simulatedAnnealing (default 10000)
+      --start-temp X       start temperature of simulatedAnnealing (default 500)
+      --end-temp X         end temperature of simulatedAnnealing (default 10)
+                           the three above also take comma-separated lists, repeat play then plays every
+                           combination on the same boards and ranks them, single play uses the first values
+      --seed-constructor N seed of the boards (default random, fixed with the seed feature)
+      --seed-action N      seed of the actions (default random, fixed with the seed feature)
+  -j, --jobs N             games of repeat play played at once (default all cores), pin it to keep timings comparable
+      --export-csv PATH    one row per game: seed, action seed, algorithm, parameters, score, elapsed time,
+                           nodes expanded
+      --export-json PATH   the same rows as a JSON array
+      --tolerance X        score points the mean may drop below the baseline in check-baseline (default 0)
+      --candidates N       simulatedAnnealing settings tune draws, the hand-picked ones included (default 16)
+      --training-games N   games the last round of tune plays each finalist on (default 200)
+                           tune searches the temperatures once per budget tier, the --simulate-number list
+                           or N/100, N/10 and N for a single N, and starts from the given temperatures
+  -h, --help               print this message";
+
+#[derive(Debug, Clone)]
+struct CliOptions {
+    game_number: usize,
+    // every combination of these lists is played in repeat play
+    simulate_numbers: Vec<usize>,
+    start_temps: Vec<f64>,
+    end_temps: Vec<f64>,
+    // None draws a seed, or takes the fixed one of the seed feature
+    seed_constructor: Option<usize>,
+    seed_action: Option<usize>,
+    jobs: usize,
+    export_csv: Option<String>,
+    export_json: Option<String>,
+    // games exported by --export-csv that check-baseline compares against
+    baseline: Option<String>,
+    tolerance: f64,
+    // tune searches simulatedAnnealing settings instead of the usual plays
+    tune: bool,
+    candidate_number: usize,
+    training_games: usize,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        CliOptions {
+            game_number: 1000,
+            simulate_numbers: vec![10000],
+            start_temps: vec![500.0],
+            end_temps: vec![10.0],
+            seed_constructor: None,
+            seed_action: None,
+            jobs: std::thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+            export_csv: None,
+            export_json: None,
+            baseline: None,
+            tolerance: 0.0,
+            tune: false,
+            candidate_number: 16,
+            training_games: 200,
+        }
+    }
+}
+
+impl CliOptions {
+    // None when help was asked for
+    fn parse<I: Iterator<Item = String>>(args: I) -> Result<Option<Self>, String> {
+        fn value<T: std::str::FromStr, I: Iterator<Item = String>>(flag: &str, args: &mut I) -> Result<T, String> {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", flag))?;
+            value
+                .parse()
+                .map_err(|_| format!("invalid value for {}: {}", flag, value))
+        }
+        fn values<T: std::str::FromStr, I: Iterator<Item = String>>(
+            flag: &str,
+            args: &mut I,
+        ) -> Result<Vec<T>, String> {
+            let list: String = value(flag, args)?;
+            list.split(',')
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid value for {}: {}", flag, value))
+                })
+                .collect()
+        }
+        let mut cli = CliOptions::default();
+        let mut args = args.peekable();
+        match args.peek().map(|arg| arg.as_str()) {
+            Some("check-baseline") => {
+                let arg = args.next().unwrap();
+                cli.baseline = Some(value(&arg, &mut args)?);
+            }
+            Some("tune") => {
+                args.next();
+                cli.tune = true;
+            }
+            _ => {}
+        }
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-n" | "--games" => cli.game_number = value(&arg, &mut args)?,
+                "--simulate-number" => cli.simulate_numbers = values(&arg, &mut args)?,
+                "--start-temp" => cli.start_temps = values(&arg, &mut args)?,
+                "--end-temp" => cli.end_temps = values(&arg, &mut args)?,
+                "--seed-constructor" => cli.seed_constructor = Some(value(&arg, &mut args)?),
+                "--seed-action" => cli.seed_action = Some(value(&arg, &mut args)?),
+                "-j" | "--jobs" => cli.jobs = value(&arg, &mut args)?,
+                "--export-csv" => cli.export_csv = Some(value(&arg, &mut args)?),
+                "--export-json" => cli.export_json = Some(value(&arg, &mut args)?),
+                "--tolerance" => cli.tolerance = value(&arg, &mut args)?,
+                "--candidates" => cli.candidate_number = value(&arg, &mut args)?,
+                "--training-games" => cli.training_games = value(&arg, &mut args)?,
+                _ => return Err(format!("unknown option: {}", arg)),
+            }
+        }
+        if cli.game_number == 0 || cli.simulate_numbers.contains(&0) || cli.jobs == 0 {
+            return Err("games, simulate number and jobs must be positive".to_string());
+        }
+        if !cli
+            .start_temps
+            .iter()
+            .chain(&cli.end_temps)
+            .all(|&temp| temp > 0.0)
+        {
+            return Err("temperatures must be positive".to_string());
+        }
+        if cli.tune && (cli.candidate_number < 2 || cli.training_games == 0) {
+            return Err("tune needs at least two candidates and one training game".to_string());
+        }
+        if cli.tune && (cli.start_temps.len() > 1 || cli.end_temps.len() > 1) {
+            return Err("tune takes a single start and end temperature".to_string());
+        }
+        if cli.tolerance.is_nan() || cli.tolerance < 0.0 {
+            return Err("tolerance must not be negative".to_string());
+        }
+        Ok(Some(cli))
+    }
+
+    // simulate numbers tune searches the temperatures at, a single one is spread over two orders of magnitude
+    fn budgetTiers(&self) -> Vec<usize> {
+        let mut tiers = match self.simulate_numbers[..] {
+            [simulate_number] => vec![simulate_number / 100, simulate_number / 10, simulate_number],
+            _ => self.simulate_numbers.clone(),
+        };
+        tiers.retain(|&simulate_number| simulate_number > 0);
+        tiers.sort_unstable();
+        tiers.dedup();
+        tiers
+    }
+
+    // (simulate_number, start_temp, end_temp) of every combination of the lists
+    fn configurations(&self) -> Vec<(usize, f64, f64)> {
+        let mut configurations = vec![];
+        for &simulate_number in self.simulate_numbers.iter() {
+            for &start_temp in self.start_temps.iter() {
+                for &end_temp in self.end_temps.iter() {
+                    configurations.push((simulate_number, start_temp, end_temp));
+                }
+            }
+        }
+        configurations
     }
 }
 
 fn main() {
+    let cli = match CliOptions::parse(std::env::args().skip(1)) {
+        Ok(Some(cli)) => cli,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("auto_move_maze: {}\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+    // read before playing, so that a bad path fails fast
+    let baseline = cli.baseline.as_ref().map(|path| {
+        readBaseline(path).unwrap_or_else(|err| {
+            eprintln!("auto_move_maze: {}", err);
+            std::process::exit(2);
+        })
+    });
+
     #[allow(unused_mut, unused_assignments)]
     let mut seed_constructor: usize = rand::thread_rng().gen();
     #[allow(unused_mut, unused_assignments)]
@@ -477,15 +1456,62 @@ fn main() {
         seed_constructor = 11216848234635351618;
         seed_action = 11216848234635351618;
     }
+    let seed_constructor = cli.seed_constructor.unwrap_or(seed_constructor);
+    let seed_action = cli.seed_action.unwrap_or(seed_action);
 
     println!("seed action: {}", seed_action);
     rnd_action::init(seed_action);
 
-    let simulate_number = 10000;
+    // a baseline check only needs the repeat play
+    if baseline.is_none() && !cli.tune {
+        println!("=====Single Play=====");
+        single_play(
+            seed_constructor,
+            cli.simulate_numbers[0],
+            cli.start_temps[0],
+            cli.end_temps[0],
+        );
+        println!();
+    }
+    let summaries = if cli.tune {
+        println!("=====Tune=====");
+        tune(
+            seed_constructor,
+            seed_action,
+            cli.candidate_number,
+            cli.training_games,
+            cli.game_number,
+            &cli.budgetTiers(),
+            cli.start_temps[0],
+            cli.end_temps[0],
+            cli.jobs,
+        )
+    } else {
+        println!("=====Repeat Play=====");
+        repeat_play(
+            seed_constructor,
+            seed_action,
+            &cli.configurations(),
+            cli.game_number,
+            cli.jobs,
+        )
+    };
 
-    println!("=====Single Play=====");
-    single_play(seed_constructor, simulate_number);
-    println!();
-    println!("=====Repeat Play=====");
-    repeat_play(seed_constructor, simulate_number);
+    let exported = |path: &str, result: std::io::Result<()>| {
+        if let Err(err) = result {
+            eprintln!("{}: {}", path, err);
+            std::process::exit(2);
+        }
+    };
+    if let Some(path) = &cli.export_csv {
+        exported(path, writeGamesCsv(path, &summaries));
+    }
+    if let Some(path) = &cli.export_json {
+        exported(path, writeGamesJson(path, &summaries));
+    }
+    if let Some(baseline) = &baseline {
+        if !checkBaseline(&summaries, baseline, cli.tolerance) {
+            std::process::exit(1);
+        }
+    }
 }
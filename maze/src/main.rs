@@ -8,6 +8,7 @@
 #![allow(clippy::neg_multiply)]
 #![allow(dead_code)]
 use rand::Rng;
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::collections::BinaryHeap;
 use std::time::Instant;
 
@@ -44,9 +45,108 @@ impl TimeKeeper {
             elapsed_time >= self.time_threshold
         }
     }
+    #[inline]
+    fn progress(&self) -> f64 {
+        let elapsed_time = self.start_time.elapsed().as_nanos() as f64 * 1e-9;
+        #[cfg(feature = "local")]
+        {
+            elapsed_time * 0.85 / self.time_threshold
+        }
+        #[cfg(not(feature = "local"))]
+        {
+            elapsed_time / self.time_threshold
+        }
+    }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+// limits of one search run, the search stops at whichever is reached first
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct SearchBudget {
+    time_threshold: Option<f64>, // s
+    nodes: Option<usize>,
+    iterations: Option<usize>,
+}
+
+impl SearchBudget {
+    fn time(time_threshold: f64) -> Self {
+        SearchBudget {
+            time_threshold: Some(time_threshold),
+            ..Default::default()
+        }
+    }
+    fn nodes(nodes: usize) -> Self {
+        SearchBudget {
+            nodes: Some(nodes),
+            ..Default::default()
+        }
+    }
+    fn iterations(iterations: usize) -> Self {
+        SearchBudget {
+            iterations: Some(iterations),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BudgetKeeper {
+    budget: SearchBudget,
+    time_keeper: TimeKeeper,
+    nodes: usize,
+    iterations: usize,
+}
+
+impl BudgetKeeper {
+    fn new(budget: SearchBudget) -> Self {
+        assert!(
+            budget.time_threshold.is_some() || budget.nodes.is_some() || budget.iterations.is_some(),
+            "search budget has no limit"
+        );
+        BudgetKeeper {
+            budget,
+            time_keeper: TimeKeeper::new(budget.time_threshold.unwrap_or(f64::INFINITY)),
+            nodes: 0,
+            iterations: 0,
+        }
+    }
+    #[inline]
+    fn addNodes(&mut self, nodes: usize) {
+        self.nodes += nodes;
+    }
+    #[inline]
+    fn addIteration(&mut self) {
+        self.iterations += 1;
+    }
+    #[inline]
+    fn isOver(&self) -> bool {
+        matches!(self.budget.nodes, Some(limit) if self.nodes >= limit)
+            || matches!(self.budget.iterations, Some(limit) if self.iterations >= limit)
+            || (self.budget.time_threshold.is_some() && self.time_keeper.isTimeOver())
+    }
+    // used fraction of the tightest limit, in [0, 1]
+    fn progress(&self) -> f64 {
+        let mut progress: f64 = 0.0;
+        if let Some(limit) = self.budget.nodes {
+            progress = progress.max(self.nodes as f64 / limit as f64);
+        }
+        if let Some(limit) = self.budget.iterations {
+            progress = progress.max(self.iterations as f64 / limit as f64);
+        }
+        if self.budget.time_threshold.is_some() {
+            progress = progress.max(self.time_keeper.progress());
+        }
+        progress.min(1.0)
+    }
+    // how far the run went past its time limit [s], 0 within the limit or without one
+    fn timeOvershoot(&self) -> f64 {
+        match self.budget.time_threshold {
+            Some(time_threshold) => ((self.time_keeper.progress() - 1.0) * time_threshold).max(0.0),
+            None => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 struct Coord {
     x_: isize,
     y_: isize,
@@ -57,92 +157,582 @@ impl Coord {
     }
 }
 
-#[derive(Debug, Clone, Eq)]
-struct MazeState {
-    points_: [[usize; W]; H],
+// Zobrist keys for (cell, kind), derived with splitmix64 so that no table has to follow the board size
+mod zobrist {
+    fn mix(mut z: u64) -> u64 {
+        z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    #[inline]
+    pub fn point(y: usize, x: usize) -> u64 {
+        mix(((y as u64) << 33) | ((x as u64) << 1))
+    }
+    #[inline]
+    pub fn character(y: usize, x: usize) -> u64 {
+        mix(((y as u64) << 33) | ((x as u64) << 1) | 1)
+    }
+}
+
+// state of a single-player game that the searches below can run on
+// Ord must compare by evaluated score, it is the BinaryHeap order of the beams
+trait SinglePlayerState: Clone + Ord {
+    type Action: Copy;
+    fn legalActions(&self) -> Vec<Self::Action>;
+    // the returned record reverts this advance when passed to undo
+    type Undo;
+    fn advance(&mut self, action: Self::Action) -> Self::Undo;
+    fn undo(&mut self, undo: Self::Undo);
+    fn isDone(&self) -> bool;
+    // the evaluation the state ranks itself by, used by the StateEvaluation evaluator
+    fn evaluateScore(&mut self);
+    fn setEvaluatedScore(&mut self, evaluated_score: ScoreType);
+    fn evaluatedScore(&self) -> ScoreType;
+    fn gameScore(&self) -> usize;
+    // equal for states in the same position, the searches drop such duplicates
+    fn zobristHash(&self) -> u64;
+}
+
+// states the diversity cap of BeamOptions::max_per_region can place on a board
+// only the layer beams need it, a puzzle without a board can still run every other search
+trait BoardPosition {
+    fn characterCoord(&self) -> Coord;
+}
+
+// what one advance on a maze changed
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct MazeUndo {
+    // point eaten on the cell that was entered, 0 if none
+    point: usize,
+    character: Coord,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BeamOptions {
+    // drop a child whose zobrist hash was already pushed at the same depth
+    dedup: bool,
+    // keep at most this many states per region at each depth of the layer beams, chokudai search ignores it
+    max_per_region: Option<usize>,
+    // side of the square regions in cells, 1 caps every character cell on its own
+    region_size: usize,
+}
+
+impl Default for BeamOptions {
+    fn default() -> Self {
+        BeamOptions {
+            dedup: false,
+            max_per_region: None,
+            region_size: 1,
+        }
+    }
+}
+
+// counts the states taken into one beam layer per region for BeamOptions::max_per_region
+struct RegionCap {
+    max_per_region: Option<usize>,
+    region_size: isize,
+    counts: FxHashMap<(isize, isize), usize>,
+    // states skipped because their region was full
+    dropped: usize,
+}
+
+impl RegionCap {
+    fn new(options: &BeamOptions) -> Self {
+        assert!(options.region_size > 0, "region_size must be positive");
+        assert!(
+            options.max_per_region != Some(0),
+            "max_per_region must be positive"
+        );
+        RegionCap {
+            max_per_region: options.max_per_region,
+            region_size: options.region_size as isize,
+            counts: FxHashMap::default(),
+            dropped: 0,
+        }
+    }
+
+    // whether a state with the character on `coord` may join the layer
+    fn admit(&mut self, coord: Coord) -> bool {
+        let max_per_region = match self.max_per_region {
+            Some(max_per_region) => max_per_region,
+            None => return true,
+        };
+        let region = (coord.y_ / self.region_size, coord.x_ / self.region_size);
+        let count = self.counts.entry(region).or_insert(0);
+        if *count == max_per_region {
+            self.dropped += 1;
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SearchStats {
+    // states generated by advance during the search
+    nodes_expanded: usize,
+    duplicates_pruned: usize,
+    // longest time a time-budgeted search ran past its deadline [s]
+    max_deadline_overshoot: f64,
+    // states dropped by BeamOptions::max_per_region, indexed by the number of moves from the searched state
+    diversity_pruned: Vec<usize>,
+}
+
+impl SearchStats {
+    fn recordDiversityPruned(&mut self, depth: usize, dropped: usize) {
+        if dropped == 0 {
+            return;
+        }
+        if self.diversity_pruned.len() <= depth {
+            self.diversity_pruned.resize(depth + 1, 0);
+        }
+        self.diversity_pruned[depth] += dropped;
+    }
+    fn merge(&mut self, other: &SearchStats) {
+        self.nodes_expanded += other.nodes_expanded;
+        self.duplicates_pruned += other.duplicates_pruned;
+        self.max_deadline_overshoot = self
+            .max_deadline_overshoot
+            .max(other.max_deadline_overshoot);
+        for (depth, &dropped) in other.diversity_pruned.iter().enumerate() {
+            self.recordDiversityPruned(depth, dropped);
+        }
+    }
+    fn recordOvershoot(&mut self, budget_keeper: &BudgetKeeper) {
+        self.max_deadline_overshoot = self
+            .max_deadline_overshoot
+            .max(budget_keeper.timeOvershoot());
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct MazeConfig {
+    h: usize,
+    w: usize,
+    end_turn: usize,
+    // points are drawn from min_point..=max_point
+    min_point: usize,
+    max_point: usize,
+}
+
+impl MazeConfig {
+    fn new(h: usize, w: usize, end_turn: usize, min_point: usize, max_point: usize) -> Self {
+        // a single cell leaves the character no move
+        assert!(h * w >= 2, "board must have at least two cells");
+        assert!(
+            min_point <= max_point,
+            "min_point must not exceed max_point"
+        );
+        // the text layout stores a point as one digit
+        assert!(max_point <= 9, "max_point must be a single digit");
+        MazeConfig {
+            h,
+            w,
+            end_turn,
+            min_point,
+            max_point,
+        }
+    }
+}
+
+impl Default for MazeConfig {
+    fn default() -> Self {
+        MazeConfig::new(H, W, END_TURN, 0, 9)
+    }
+}
+
+#[derive(Debug)]
+enum MazeParseError {
+    Io(std::io::Error),
+    MissingLine(&'static str),
+    InvalidHeader {
+        line: usize,
+        expected: &'static str,
+        found: String,
+    },
+    EmptyGrid,
+    SingleCell,
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidCharacter {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    CharacterCount(usize),
+    TurnOutOfRange {
+        turn: usize,
+        end_turn: usize,
+    },
+}
+
+impl std::fmt::Display for MazeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MazeParseError::Io(err) => write!(f, "failed to read maze: {}", err),
+            MazeParseError::MissingLine(name) => write!(f, "missing `{}` line", name),
+            MazeParseError::InvalidHeader {
+                line,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "line {}: expected `{}: <number>`, found `{}`",
+                    line, expected, found
+                )
+            }
+            MazeParseError::EmptyGrid => write!(f, "grid has no rows"),
+            MazeParseError::SingleCell => write!(f, "grid needs at least two cells"),
+            MazeParseError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "line {}: expected {} cells, found {}",
+                    line, expected, found
+                )
+            }
+            MazeParseError::InvalidCharacter {
+                line,
+                column,
+                found,
+            } => {
+                write!(
+                    f,
+                    "line {}, column {}: unexpected character `{}`",
+                    line, column, found
+                )
+            }
+            MazeParseError::CharacterCount(count) => write!(f, "expected exactly one `@`, found {}", count),
+            MazeParseError::TurnOutOfRange { turn, end_turn } => {
+                write!(f, "turn {} is beyond end turn {}", turn, end_turn)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MazeParseError {}
+
+impl From<std::io::Error> for MazeParseError {
+    fn from(err: std::io::Error) -> Self {
+        MazeParseError::Io(err)
+    }
+}
+
+// the board both maze layouts play on: points left, character, turn and score, with their zobrist hash
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct MazeGrid {
+    config_: MazeConfig,
+    // row-major, points_[y * w + x]
+    points_: Vec<usize>,
     turn_: usize,
     character_: Coord,
     game_score_: usize,
-    evaluated_score_: ScoreType,
-    first_action_: isize,
+    hash_: u64,
 }
 
-impl MazeState {
-    fn new() -> Self {
-        #[allow(unused_assignments)]
-        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(rand::thread_rng().gen());
-        #[cfg(feature = "seed")]
-        {
-            let seed = 12;
-            eprintln!("seed: {}", seed);
-            rng = rand::SeedableRng::seed_from_u64(seed);
-        }
-
-        let mut character_ = Coord::new();
-        character_.x_ = rng.gen_range(0, W) as isize;
-        character_.y_ = rng.gen_range(0, H) as isize;
-        let mut points_ = [[0; W]; H];
-        for y in 0..H {
-            for x in 0..W {
-                if x as isize == character_.x_ && y as isize == character_.y_ {
-                    continue;
+impl MazeGrid {
+    fn new(config: MazeConfig, points: Vec<usize>, turn: usize, character: Coord, game_score: usize) -> Self {
+        let mut grid = MazeGrid {
+            config_: config,
+            points_: points,
+            turn_: turn,
+            character_: character,
+            game_score_: game_score,
+            hash_: 0,
+        };
+        grid.hash_ = zobrist::character(character.y_ as usize, character.x_ as usize);
+        for y in 0..config.h {
+            for x in 0..config.w {
+                if grid.points_[y * config.w + x] > 0 {
+                    grid.hash_ ^= zobrist::point(y, x);
                 }
-                points_[y][x] = rng.gen_range(0, 10);
             }
         }
-        MazeState {
-            points_,
-            turn_: 0,
-            character_,
-            game_score_: 0,
-            evaluated_score_: 0,
-            first_action_: -1,
-        }
+        grid
     }
     fn isDone(&self) -> bool {
-        self.turn_ == END_TURN
+        self.turn_ == self.config_.end_turn
     }
-    fn advance(&mut self, action: usize) {
+    fn advance(&mut self, action: usize) -> MazeUndo {
+        let undo = MazeUndo {
+            point: 0,
+            character: self.character_,
+        };
+        self.hash_ ^= zobrist::character(self.character_.y_ as usize, self.character_.x_ as usize);
         self.character_.x_ += dx[action];
         self.character_.y_ += dy[action];
-        let point = &mut self.points_[self.character_.y_ as usize][self.character_.x_ as usize] as *mut usize;
-        unsafe {
-            if *point > 0 {
-                self.game_score_ += *point;
-                *point = 0;
-            }
+        let (y, x) = (self.character_.y_ as usize, self.character_.x_ as usize);
+        self.hash_ ^= zobrist::character(y, x);
+        let point = std::mem::replace(&mut self.points_[y * self.config_.w + x], 0);
+        if point > 0 {
+            self.game_score_ += point;
+            self.hash_ ^= zobrist::point(y, x);
         }
         self.turn_ += 1;
+        MazeUndo { point, ..undo }
     }
-    fn legalActions(&self) -> Vec<usize> {
-        let mut actions = vec![];
+    fn undo(&mut self, undo: MazeUndo) {
+        let (y, x) = (self.character_.y_ as usize, self.character_.x_ as usize);
+        if undo.point > 0 {
+            self.points_[y * self.config_.w + x] = undo.point;
+            self.game_score_ -= undo.point;
+            self.hash_ ^= zobrist::point(y, x);
+        }
+        self.hash_ ^= zobrist::character(y, x);
+        self.character_ = undo.character;
+        self.hash_ ^= zobrist::character(self.character_.y_ as usize, self.character_.x_ as usize);
+        self.turn_ -= 1;
+    }
+    // actions that keep the character on the board, with the index of the cell each one enters
+    fn movesInside(&self) -> Vec<(usize, usize)> {
+        let mut moves = vec![];
         for action in 0..4 {
             let ty = self.character_.y_ + dy[action];
             let tx = self.character_.x_ + dx[action];
-            if 0 <= ty && ty < H as isize && 0 <= tx && tx < W as isize {
-                actions.push(action);
+            if 0 <= ty && ty < self.config_.h as isize && 0 <= tx && tx < self.config_.w as isize {
+                moves.push((action, ty as usize * self.config_.w + tx as usize));
             }
         }
-        actions
+        moves
     }
-    fn toString(&self) {
-        println!("turn: {}", self.turn_);
-        println!("score: {}", self.game_score_);
-        for y in 0..H {
-            for x in 0..W {
+    // upper bound on the points still to be eaten: each remaining turn eats at most one point,
+    // taken from a cell no farther than the remaining turns
+    // walls only make cells farther, so it holds on every layout
+    fn remainingPointBound(&self) -> usize {
+        let turns_left = self.config_.end_turn - self.turn_;
+        let mut reachable_points = vec![];
+        for y in 0..self.config_.h {
+            for x in 0..self.config_.w {
+                let point = self.points_[y * self.config_.w + x];
+                let distance =
+                    (y as isize - self.character_.y_).unsigned_abs() + (x as isize - self.character_.x_).unsigned_abs();
+                if point > 0 && distance <= turns_left {
+                    reachable_points.push(point);
+                }
+            }
+        }
+        reachable_points.sort_unstable_by(|a, b| b.cmp(a));
+        reachable_points.iter().take(turns_left).sum()
+    }
+    // the layout read by MazeState::parse, cells for which is_wall holds are drawn as `#`
+    fn write(&self, f: &mut std::fmt::Formatter, is_wall: impl Fn(usize) -> bool) -> std::fmt::Result {
+        writeln!(f, "turn: {}", self.turn_)?;
+        writeln!(f, "score: {}", self.game_score_)?;
+        for y in 0..self.config_.h {
+            for x in 0..self.config_.w {
+                let index = y * self.config_.w + x;
                 if self.character_.y_ == y as isize && self.character_.x_ == x as isize {
-                    print!("@");
-                } else if self.points_[y][x] > 0 {
-                    print!("{}", self.points_[y][x]);
+                    write!(f, "@")?;
+                } else if is_wall(index) {
+                    write!(f, "#")?;
+                } else if self.points_[index] > 0 {
+                    write!(f, "{}", self.points_[index])?;
                 } else {
-                    print!(".");
+                    write!(f, ".")?;
                 }
             }
-            println!();
+            writeln!(f)?;
         }
+        Ok(())
+    }
+}
+
+// a maze the binary can generate and play
+trait MazeGame: SinglePlayerState<Action = usize, Undo = MazeUndo> + BoardPosition + Send + Sync + 'static {
+    // same (config, seed) always generates the same board
+    fn fromSeed(config: MazeConfig, seed: u64) -> Self;
+    fn grid(&self) -> &MazeGrid;
+}
+
+#[derive(Debug, Clone, Eq)]
+struct MazeState {
+    grid_: MazeGrid,
+    evaluated_score_: ScoreType,
+}
+
+impl MazeState {
+    fn new(config: MazeConfig) -> Self {
+        #[allow(unused_mut, unused_assignments)]
+        let mut seed: u64 = rand::thread_rng().gen();
+        #[cfg(feature = "seed")]
+        {
+            seed = 12;
+            eprintln!("seed: {}", seed);
+        }
+        MazeState::from_seed(config, seed)
+    }
+    // same (config, seed) always generates the same board
+    fn from_seed(config: MazeConfig, seed: u64) -> Self {
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(seed);
+        let mut character_ = Coord::new();
+        character_.x_ = rng.gen_range(0, config.w) as isize;
+        character_.y_ = rng.gen_range(0, config.h) as isize;
+        let mut points_ = vec![0; config.h * config.w];
+        for y in 0..config.h {
+            for x in 0..config.w {
+                if x as isize == character_.x_ && y as isize == character_.y_ {
+                    continue;
+                }
+                points_[y * config.w + x] = rng.gen_range(config.min_point, config.max_point + 1);
+            }
+        }
+        MazeState {
+            grid_: MazeGrid::new(config, points_, 0, character_, 0),
+            evaluated_score_: 0,
+        }
+    }
+    // reads the layout written by toString
+    // h and w are taken from the grid, the rest of the config from `config`
+    fn parse(text: &str, config: MazeConfig) -> Result<Self, MazeParseError> {
+        let mut lines = text.lines().map(|line| line.trim_end()).enumerate();
+        let mut header = |name: &'static str| -> Result<usize, MazeParseError> {
+            let (i, line) = lines.next().ok_or(MazeParseError::MissingLine(name))?;
+            line.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix(':'))
+                .and_then(|rest| rest.trim().parse().ok())
+                .ok_or_else(|| MazeParseError::InvalidHeader {
+                    line: i + 1,
+                    expected: name,
+                    found: line.to_string(),
+                })
+        };
+        let turn_ = header("turn")?;
+        let game_score_ = header("score")?;
+
+        let rows: Vec<(usize, &str)> = lines.filter(|(_, line)| !line.is_empty()).collect();
+        if rows.is_empty() {
+            return Err(MazeParseError::EmptyGrid);
+        }
+        let h = rows.len();
+        let w = rows[0].1.chars().count();
+        let mut points_ = vec![0; h * w];
+        let mut characters = vec![];
+        for (y, &(i, row)) in rows.iter().enumerate() {
+            let found = row.chars().count();
+            if found != w {
+                return Err(MazeParseError::RaggedRow {
+                    line: i + 1,
+                    expected: w,
+                    found,
+                });
+            }
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '@' => characters.push(Coord {
+                        x_: x as isize,
+                        y_: y as isize,
+                    }),
+                    '.' => {}
+                    '1'..='9' => points_[y * w + x] = c.to_digit(10).unwrap() as usize,
+                    _ => {
+                        return Err(MazeParseError::InvalidCharacter {
+                            line: i + 1,
+                            column: x + 1,
+                            found: c,
+                        })
+                    }
+                }
+            }
+        }
+        if characters.len() != 1 {
+            return Err(MazeParseError::CharacterCount(characters.len()));
+        }
+        if h * w < 2 {
+            return Err(MazeParseError::SingleCell);
+        }
+        let config = MazeConfig::new(h, w, config.end_turn, config.min_point, config.max_point);
+        if turn_ > config.end_turn {
+            return Err(MazeParseError::TurnOutOfRange {
+                turn: turn_,
+                end_turn: config.end_turn,
+            });
+        }
+        Ok(MazeState {
+            grid_: MazeGrid::new(
+                config,
+                points_,
+                turn_,
+                characters.pop().unwrap(),
+                game_score_,
+            ),
+            evaluated_score_: 0,
+        })
+    }
+    fn fromReader<R: std::io::Read>(mut reader: R, config: MazeConfig) -> Result<Self, MazeParseError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        MazeState::parse(&text, config)
+    }
+    // "-" reads from stdin
+    fn load(path: &str, config: MazeConfig) -> Result<Self, MazeParseError> {
+        if path == "-" {
+            MazeState::fromReader(std::io::stdin(), config)
+        } else {
+            MazeState::fromReader(std::fs::File::open(path)?, config)
+        }
+    }
+    fn toString(&self) {
+        print!("{}", self);
+    }
+}
+
+impl MazeGame for MazeState {
+    fn fromSeed(config: MazeConfig, seed: u64) -> Self {
+        MazeState::from_seed(config, seed)
+    }
+    fn grid(&self) -> &MazeGrid {
+        &self.grid_
+    }
+}
+
+impl SinglePlayerState for MazeState {
+    type Action = usize;
+    type Undo = MazeUndo;
+    fn isDone(&self) -> bool {
+        self.grid_.isDone()
+    }
+    fn advance(&mut self, action: usize) -> MazeUndo {
+        self.grid_.advance(action)
+    }
+    fn undo(&mut self, undo: MazeUndo) {
+        self.grid_.undo(undo);
+    }
+    fn legalActions(&self) -> Vec<usize> {
+        self.grid_
+            .movesInside()
+            .into_iter()
+            .map(|(action, _)| action)
+            .collect()
     }
     fn evaluateScore(&mut self) {
-        self.evaluated_score_ = self.game_score_ as isize;
+        self.evaluated_score_ = self.grid_.game_score_ as isize;
+    }
+    fn evaluatedScore(&self) -> ScoreType {
+        self.evaluated_score_
+    }
+    fn setEvaluatedScore(&mut self, evaluated_score: ScoreType) {
+        self.evaluated_score_ = evaluated_score;
+    }
+    fn gameScore(&self) -> usize {
+        self.grid_.game_score_
+    }
+    fn zobristHash(&self) -> u64 {
+        self.grid_.hash_
+    }
+}
+
+impl BoardPosition for MazeState {
+    fn characterCoord(&self) -> Coord {
+        self.grid_.character_
     }
 }
 
@@ -154,15 +744,7 @@ impl std::cmp::PartialEq for MazeState {
 
 impl std::cmp::PartialOrd for MazeState {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if self.evaluated_score_ == other.evaluated_score_ {
-            Some(std::cmp::Ordering::Equal)
-        } else if self.evaluated_score_ > other.evaluated_score_ {
-            Some(std::cmp::Ordering::Greater)
-        } else if self.evaluated_score_ < other.evaluated_score_ {
-            Some(std::cmp::Ordering::Less)
-        } else {
-            None
-        }
+        Some(self.cmp(other))
     }
 }
 
@@ -178,212 +760,3191 @@ impl std::cmp::Ord for MazeState {
     }
 }
 
-fn randomAction(state: &MazeState) -> usize {
-    let legal_actions = state.legalActions();
-    let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(0);
-    legal_actions[rng.gen_range(0, legal_actions.len())]
+impl std::fmt::Display for MazeState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.grid_.write(f, |_| false)
+    }
 }
 
-fn greedyAction(state: &MazeState) -> usize {
-    let legal_actions = state.legalActions();
-    let mut best_score = -INF;
-    let mut best_action = -1_isize;
-    for &action in &legal_actions {
-        let mut now_state = state.clone();
-        now_state.advance(action);
-        now_state.evaluateScore();
-        if now_state.evaluated_score_ > best_score {
-            best_score = now_state.evaluated_score_;
-            best_action = action as isize;
-        }
-    }
-    best_action as usize
+#[derive(Debug, Clone, Eq)]
+struct WallMazeState {
+    grid_: MazeGrid,
+    // row-major like the points
+    walls_: Vec<bool>,
+    evaluated_score_: ScoreType,
 }
 
-fn beamSearchAction(state: &MazeState, beam_width: usize, beam_depth: usize) -> usize {
-    let mut now_beam = BinaryHeap::new();
-    let mut best_state = &MazeState::new(); // initialize
-    now_beam.push(state.clone());
+impl WallMazeState {
+    fn new(config: MazeConfig) -> Self {
+        #[allow(unused_mut, unused_assignments)]
+        let mut seed: u64 = rand::thread_rng().gen();
+        #[cfg(feature = "seed")]
+        {
+            seed = 12;
+            eprintln!("seed: {}", seed);
+        }
+        WallMazeState::from_seed(config, seed)
+    }
+    fn from_seed(config: MazeConfig, seed: u64) -> Self {
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(seed);
+        let mut character_ = Coord::new();
+        character_.x_ = rng.gen_range(0, config.w) as isize;
+        character_.y_ = rng.gen_range(0, config.h) as isize;
 
-    for t in 0..beam_depth {
-        let mut next_beam = BinaryHeap::new();
-        for _ in 0..beam_width {
-            if now_beam.is_empty() {
-                break;
-            }
-            let now_state = now_beam.pop().unwrap();
-            let legal_actions = now_state.legalActions();
-            for &action in &legal_actions {
-                let mut next_state = now_state.clone();
-                next_state.advance(action);
-                next_state.evaluateScore();
-                if t == 0 {
-                    next_state.first_action_ = action as isize;
+        // stick-knocking: a stick stands on every odd cell and falls to one side
+        // sticks below the first row never fall up, so no closed room is made
+        // on an even side the last row or column gets no sticks, they could close off a corner there
+        let mut walls_ = vec![false; config.h * config.w];
+        for y in (1..config.h.saturating_sub(1)).step_by(2) {
+            for x in (1..config.w.saturating_sub(1)).step_by(2) {
+                if x as isize == character_.x_ && y as isize == character_.y_ {
+                    continue;
+                }
+                walls_[y * config.w + x] = true;
+                let direction_size = if y == 1 { 4 } else { 3 };
+                let directions: Vec<usize> = (0..direction_size)
+                    .filter(|&d| {
+                        let ty = y as isize + dy[d];
+                        let tx = x as isize + dx[d];
+                        0 <= ty && ty < config.h as isize && 0 <= tx && tx < config.w as isize
+                    })
+                    .collect();
+                if directions.is_empty() {
+                    continue;
+                }
+                let direction = directions[rng.gen_range(0, directions.len())];
+                let ty = y as isize + dy[direction];
+                let tx = x as isize + dx[direction];
+                if tx == character_.x_ && ty == character_.y_ {
+                    continue;
                 }
-                next_beam.push(next_state);
+                walls_[ty as usize * config.w + tx as usize] = true;
             }
         }
 
-        now_beam = next_beam;
-        best_state = now_beam.peek().unwrap();
-        if best_state.isDone() {
-            break;
-        }
-    }
-    best_state.first_action_ as usize
-}
-
-fn beamSearchActionWithTimeThreshold(state: &MazeState, beam_width: usize, time_threshold: f64) -> usize {
-    let mut now_beam = BinaryHeap::new();
-    let mut best_state = &MazeState::new(); // initialize
-    now_beam.push(state.clone());
-    let time_keeper = TimeKeeper::new(time_threshold);
-
-    for t in 0.. {
-        let mut next_beam = BinaryHeap::new();
-        for _ in 0..beam_width {
-            if now_beam.is_empty() {
-                break;
-            }
-            let now_state = now_beam.pop().unwrap();
-            let legal_actions = now_state.legalActions();
-            for &action in &legal_actions {
-                let mut next_state = now_state.clone();
-                next_state.advance(action);
-                next_state.evaluateScore();
-                if t == 0 {
-                    next_state.first_action_ = action as isize;
+        let mut points_ = vec![0; config.h * config.w];
+        for y in 0..config.h {
+            for x in 0..config.w {
+                if walls_[y * config.w + x] || (x as isize == character_.x_ && y as isize == character_.y_) {
+                    continue;
                 }
-                next_beam.push(next_state);
+                points_[y * config.w + x] = rng.gen_range(config.min_point, config.max_point + 1);
             }
         }
-
-        now_beam = next_beam;
-        best_state = now_beam.peek().unwrap();
-        if best_state.isDone() || time_keeper.isTimeOver() {
-            break;
+        WallMazeState {
+            grid_: MazeGrid::new(config, points_, 0, character_, 0),
+            walls_,
+            evaluated_score_: 0,
         }
     }
-    best_state.first_action_ as usize
-}
-
-fn chokudaiSearchAction(state: &MazeState, beam_width: usize, beam_depth: usize, beam_number: usize) -> isize {
-    let mut beam = vec![BinaryHeap::new(); beam_depth + 1];
-    beam[0].push(state.clone());
-
-    for _ in 0..beam_number {
-        for t in 0..beam_depth {
-            for _ in 0..beam_width {
-                if beam[t].is_empty() {
-                    break;
-                }
-                let now_state = beam[t].peek().unwrap().clone();
-                if now_state.isDone() {
-                    break;
+    // BFS distance from the character to the nearest remaining point, h * w if none is reachable
+    fn getDistanceToNearestPoint(&self) -> usize {
+        let grid = &self.grid_;
+        let (h, w) = (grid.config_.h, grid.config_.w);
+        let start = grid.character_.y_ as usize * w + grid.character_.x_ as usize;
+        let mut dist = vec![usize::MAX; h * w];
+        let mut queue = std::collections::VecDeque::new();
+        dist[start] = 0;
+        queue.push_back(start);
+        while let Some(now) = queue.pop_front() {
+            if grid.points_[now] > 0 {
+                return dist[now];
+            }
+            let (y, x) = ((now / w) as isize, (now % w) as isize);
+            for action in 0..4 {
+                let ty = y + dy[action];
+                let tx = x + dx[action];
+                if ty < 0 || ty >= h as isize || tx < 0 || tx >= w as isize {
+                    continue;
                 }
-                beam[t].pop();
-
-                let legal_actions = now_state.legalActions();
-                for &action in &legal_actions {
-                    let mut next_state = now_state.clone();
-                    next_state.advance(action);
-                    next_state.evaluateScore();
-                    if t == 0 {
-                        next_state.first_action_ = action as isize;
-                    }
-                    beam[t + 1].push(next_state);
+                let next = ty as usize * w + tx as usize;
+                if self.walls_[next] || dist[next] != usize::MAX {
+                    continue;
                 }
+                dist[next] = dist[now] + 1;
+                queue.push_back(next);
             }
         }
+        h * w
     }
-    for t in (0..=beam_depth).rev() {
-        let now_beam = &beam[t];
-        if !now_beam.is_empty() {
-            return now_beam.peek().unwrap().first_action_;
-        }
+    fn toString(&self) {
+        print!("{}", self);
     }
-    -1
 }
 
-fn chokudaiSearchActionWithTimeThreshold(
-    state: &MazeState,
-    beam_width: usize,
+impl MazeGame for WallMazeState {
+    fn fromSeed(config: MazeConfig, seed: u64) -> Self {
+        WallMazeState::from_seed(config, seed)
+    }
+    fn grid(&self) -> &MazeGrid {
+        &self.grid_
+    }
+}
+
+impl SinglePlayerState for WallMazeState {
+    type Action = usize;
+    type Undo = MazeUndo;
+    fn isDone(&self) -> bool {
+        self.grid_.isDone()
+    }
+    fn advance(&mut self, action: usize) -> MazeUndo {
+        self.grid_.advance(action)
+    }
+    fn undo(&mut self, undo: MazeUndo) {
+        self.grid_.undo(undo);
+    }
+    fn legalActions(&self) -> Vec<usize> {
+        self.grid_
+            .movesInside()
+            .into_iter()
+            .filter(|&(_, cell)| !self.walls_[cell])
+            .map(|(action, _)| action)
+            .collect()
+    }
+    // a point is worth more than any distance, the distance only breaks ties
+    fn evaluateScore(&mut self) {
+        let cells = (self.grid_.config_.h * self.grid_.config_.w) as ScoreType;
+        self.evaluated_score_ =
+            self.grid_.game_score_ as ScoreType * cells - self.getDistanceToNearestPoint() as ScoreType;
+    }
+    fn evaluatedScore(&self) -> ScoreType {
+        self.evaluated_score_
+    }
+    fn setEvaluatedScore(&mut self, evaluated_score: ScoreType) {
+        self.evaluated_score_ = evaluated_score;
+    }
+    fn gameScore(&self) -> usize {
+        self.grid_.game_score_
+    }
+    fn zobristHash(&self) -> u64 {
+        self.grid_.hash_
+    }
+}
+
+impl BoardPosition for WallMazeState {
+    fn characterCoord(&self) -> Coord {
+        self.grid_.character_
+    }
+}
+
+impl std::cmp::PartialEq for WallMazeState {
+    fn eq(&self, other: &Self) -> bool {
+        self.evaluated_score_ == other.evaluated_score_
+    }
+}
+
+impl std::cmp::PartialOrd for WallMazeState {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::cmp::Ord for WallMazeState {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.evaluated_score_.cmp(&other.evaluated_score_)
+    }
+}
+
+impl std::fmt::Display for WallMazeState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.grid_.write(f, |cell| self.walls_[cell])
+    }
+}
+
+// ranks the states of a beam or chokudai search by setting their evaluated score
+trait Evaluator<S> {
+    fn evaluate(&self, state: &mut S);
+}
+
+// whatever the state's own evaluateScore computes
+#[derive(Debug, Clone, Copy, Default)]
+struct StateEvaluation;
+
+impl<S: SinglePlayerState> Evaluator<S> for StateEvaluation {
+    fn evaluate(&self, state: &mut S) {
+        state.evaluateScore();
+    }
+}
+
+// the game score alone
+#[derive(Debug, Clone, Copy, Default)]
+struct RawScore;
+
+impl<S: SinglePlayerState> Evaluator<S> for RawScore {
+    fn evaluate(&self, state: &mut S) {
+        state.setEvaluatedScore(state.gameScore() as ScoreType);
+    }
+}
+
+// the game score first, ties broken by the distance to the nearest remaining point
+#[derive(Debug, Clone, Copy, Default)]
+struct NearestPointDistance;
+
+impl Evaluator<MazeState> for NearestPointDistance {
+    fn evaluate(&self, state: &mut MazeState) {
+        let grid = &state.grid_;
+        let (h, w) = (grid.config_.h, grid.config_.w);
+        let mut distance = h * w;
+        for y in 0..h {
+            for x in 0..w {
+                if grid.points_[y * w + x] > 0 {
+                    distance = distance.min(
+                        (y as isize - grid.character_.y_).unsigned_abs()
+                            + (x as isize - grid.character_.x_).unsigned_abs(),
+                    );
+                }
+            }
+        }
+        state.evaluated_score_ = grid.game_score_ as ScoreType * (h * w) as ScoreType - distance as ScoreType;
+    }
+}
+
+impl Evaluator<WallMazeState> for NearestPointDistance {
+    fn evaluate(&self, state: &mut WallMazeState) {
+        state.evaluateScore();
+    }
+}
+
+// the game score plus every point within `radius` cells, weighted by decay^distance
+// evaluated in thousandths of a point, distances ignore walls
+#[derive(Debug, Clone, Copy)]
+struct DecayedPoints {
+    radius: usize,
+    decay: f64,
+}
+
+impl<S: MazeGame> Evaluator<S> for DecayedPoints {
+    fn evaluate(&self, state: &mut S) {
+        let grid = state.grid();
+        let (h, w) = (grid.config_.h as isize, grid.config_.w as isize);
+        let radius = self.radius as isize;
+        let (cy, cx) = (grid.character_.y_, grid.character_.x_);
+        let mut value = grid.game_score_ as f64;
+        for y in (cy - radius).max(0)..=(cy + radius).min(h - 1) {
+            let rest = radius - (y - cy).abs();
+            for x in (cx - rest).max(0)..=(cx + rest).min(w - 1) {
+                let point = grid.points_[(y * w + x) as usize];
+                if point > 0 {
+                    value += point as f64 * self.decay.powi(((y - cy).abs() + (x - cx).abs()) as i32);
+                }
+            }
+        }
+        state.setEvaluatedScore((value * 1000.0).round() as ScoreType);
+    }
+}
+
+fn randomAction<S: SinglePlayerState, R: Rng>(state: &S, rng: &mut R) -> S::Action {
+    let legal_actions = state.legalActions();
+    legal_actions[rng.gen_range(0, legal_actions.len())]
+}
+
+fn greedyAction<S: SinglePlayerState>(state: &S) -> S::Action {
+    let legal_actions = state.legalActions();
+    let mut best_score = -INF;
+    let mut best_action = None;
+    for &action in &legal_actions {
+        let mut now_state = state.clone();
+        now_state.advance(action);
+        now_state.evaluateScore();
+        if now_state.evaluatedScore() > best_score {
+            best_score = now_state.evaluatedScore();
+            best_action = Some(action);
+        }
+    }
+    best_action.unwrap()
+}
+
+// parent links of every pushed child, so that the plan of any beam state can be read back
+struct ActionHistory<A> {
+    nodes: Vec<(Option<usize>, A)>,
+}
+
+impl<A: Copy> ActionHistory<A> {
+    fn new() -> Self {
+        ActionHistory { nodes: vec![] }
+    }
+    fn push(&mut self, parent: Option<usize>, action: A) -> usize {
+        self.nodes.push((parent, action));
+        self.nodes.len() - 1
+    }
+    fn plan(&self, mut node: Option<usize>) -> Vec<A> {
+        let mut actions = vec![];
+        while let Some(id) = node {
+            let (parent, action) = self.nodes[id];
+            actions.push(action);
+            node = parent;
+        }
+        actions.reverse();
+        actions
+    }
+}
+
+// beam entry, ordered by the state alone so the beams pick exactly what a BinaryHeap<S> would
+#[derive(Clone)]
+struct PlanNode<S> {
+    state: S,
+    node: Option<usize>,
+}
+
+impl<S: SinglePlayerState> PlanNode<S> {
+    fn root(state: &S) -> Self {
+        PlanNode {
+            state: state.clone(),
+            node: None,
+        }
+    }
+}
+
+impl<S: Ord> std::cmp::PartialEq for PlanNode<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.state == other.state
+    }
+}
+
+impl<S: Ord> std::cmp::Eq for PlanNode<S> {}
+
+impl<S: Ord> std::cmp::PartialOrd for PlanNode<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: Ord> std::cmp::Ord for PlanNode<S> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.state.cmp(&other.state)
+    }
+}
+
+// pushes every child of `now` that survives deduplication into `next_beam`
+// returns the number of children generated
+fn expandPlanNode<S: SinglePlayerState, E: Evaluator<S>>(
+    now: &PlanNode<S>,
+    next_beam: &mut BinaryHeap<PlanNode<S>>,
+    seen: &mut FxHashSet<u64>,
+    history: &mut ActionHistory<S::Action>,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> usize {
+    let legal_actions = now.state.legalActions();
+    stats.nodes_expanded += legal_actions.len();
+    for &action in &legal_actions {
+        let mut next_state = now.state.clone();
+        next_state.advance(action);
+        if options.dedup && !seen.insert(next_state.zobristHash()) {
+            stats.duplicates_pruned += 1;
+            continue;
+        }
+        evaluator.evaluate(&mut next_state);
+        next_beam.push(PlanNode {
+            state: next_state,
+            node: Some(history.push(now.node, action)),
+        });
+    }
+    legal_actions.len()
+}
+
+// best action sequence found and the game score it reaches
+fn beamSearchPlan<S: SinglePlayerState + BoardPosition, E: Evaluator<S>>(
+    state: &S,
+    beam_width: usize,
     beam_depth: usize,
-    time_threshold: f64,
-) -> isize {
-    let mut beam = vec![BinaryHeap::new(); beam_depth + 1];
-    beam[0].push(state.clone());
-    let time_keeper = TimeKeeper::new(time_threshold);
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> (Vec<S::Action>, usize) {
+    let mut history = ActionHistory::new();
+    let mut now_beam = BinaryHeap::new();
+    let mut best = PlanNode::root(state);
+    now_beam.push(best.clone());
 
-    loop {
-        for t in 0..beam_depth {
-            for _ in 0..beam_width {
-                if beam[t].is_empty() {
-                    break;
+    for t in 0..beam_depth {
+        let mut next_beam = BinaryHeap::new();
+        let mut seen = FxHashSet::default();
+        let mut region_cap = RegionCap::new(options);
+        let mut selected = 0;
+        while selected < beam_width {
+            let now = match now_beam.pop() {
+                Some(now) => now,
+                None => break,
+            };
+            if !region_cap.admit(now.state.characterCoord()) {
+                continue;
+            }
+            selected += 1;
+            expandPlanNode(
+                &now,
+                &mut next_beam,
+                &mut seen,
+                &mut history,
+                evaluator,
+                options,
+                stats,
+            );
+        }
+        stats.recordDiversityPruned(t, region_cap.dropped);
+
+        now_beam = next_beam;
+        best = now_beam.peek().unwrap().clone();
+        if best.state.isDone() {
+            break;
+        }
+    }
+    (history.plan(best.node), best.state.gameScore())
+}
+
+fn beamSearchAction<S: SinglePlayerState + BoardPosition, E: Evaluator<S>>(
+    state: &S,
+    beam_width: usize,
+    beam_depth: usize,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> S::Action {
+    beamSearchPlan(state, beam_width, beam_depth, evaluator, options, stats).0[0]
+}
+
+fn beamSearchPlanWithTimeThreshold<S: SinglePlayerState + BoardPosition, E: Evaluator<S>>(
+    state: &S,
+    beam_width: usize,
+    budget: SearchBudget,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> (Vec<S::Action>, usize) {
+    let mut history = ActionHistory::new();
+    let mut now_beam = BinaryHeap::new();
+    let mut best = PlanNode::root(state);
+    now_beam.push(best.clone());
+    let mut budget_keeper = BudgetKeeper::new(budget);
+    let mut t = 0;
+
+    'search: loop {
+        let mut next_beam = BinaryHeap::new();
+        let mut seen = FxHashSet::default();
+        let mut region_cap = RegionCap::new(options);
+        let mut selected = 0;
+        while selected < beam_width {
+            let now = match now_beam.pop() {
+                Some(now) => now,
+                None => break,
+            };
+            if !region_cap.admit(now.state.characterCoord()) {
+                continue;
+            }
+            selected += 1;
+            budget_keeper.addNodes(expandPlanNode(
+                &now,
+                &mut next_beam,
+                &mut seen,
+                &mut history,
+                evaluator,
+                options,
+                stats,
+            ));
+            // out of budget in the middle of a layer: keep the better of the last full layer and this part
+            if budget_keeper.isOver() {
+                if let Some(partial_best) = next_beam.peek() {
+                    if best.node.is_none() || *partial_best > best {
+                        best = partial_best.clone();
+                    }
                 }
-                let now_state = beam[t].peek().unwrap().clone();
-                if now_state.isDone() {
-                    break;
+                stats.recordDiversityPruned(t, region_cap.dropped);
+                break 'search;
+            }
+        }
+        stats.recordDiversityPruned(t, region_cap.dropped);
+        budget_keeper.addIteration();
+        t += 1;
+
+        now_beam = next_beam;
+        best = now_beam.peek().unwrap().clone();
+        if best.state.isDone() || budget_keeper.isOver() {
+            break;
+        }
+    }
+    stats.recordOvershoot(&budget_keeper);
+    (history.plan(best.node), best.state.gameScore())
+}
+
+fn beamSearchActionWithTimeThreshold<S: SinglePlayerState + BoardPosition, E: Evaluator<S>>(
+    state: &S,
+    beam_width: usize,
+    budget: SearchBudget,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> S::Action {
+    beamSearchPlanWithTimeThreshold(state, beam_width, budget, evaluator, options, stats).0[0]
+}
+
+// children of one beam state with their actions, None if it was never expanded
+type PlanChildren<S> = Option<Vec<(<S as SinglePlayerState>::Action, S)>>;
+
+// children of every state in `selected`, generated on up to `threads` scoped worker threads
+// each worker takes a contiguous chunk, so the result is in the order of `selected` whatever the thread count
+// a worker that sees the time keeper run out leaves the rest of its chunk as None, except that the first
+// selected state is always expanded, so that a search out of time still has a move to return
+fn parallelExpand<S, E>(
+    selected: &[PlanNode<S>],
+    threads: usize,
+    time_keeper: Option<&TimeKeeper>,
+    evaluator: &E,
+) -> Vec<PlanChildren<S>>
+where
+    S: SinglePlayerState + Send + Sync,
+    S::Action: Send,
+    E: Evaluator<S> + Sync,
+{
+    let expand = |first: usize, chunk: &[PlanNode<S>]| -> Vec<PlanChildren<S>> {
+        let mut out_of_time = false;
+        chunk
+            .iter()
+            .enumerate()
+            .map(|(i, now)| {
+                out_of_time =
+                    out_of_time || (first + i > 0 && time_keeper.is_some_and(|time_keeper| time_keeper.isTimeOver()));
+                if out_of_time {
+                    return None;
                 }
-                beam[t].pop();
+                let children = now
+                    .state
+                    .legalActions()
+                    .into_iter()
+                    .map(|action| {
+                        let mut next_state = now.state.clone();
+                        next_state.advance(action);
+                        evaluator.evaluate(&mut next_state);
+                        (action, next_state)
+                    })
+                    .collect();
+                Some(children)
+            })
+            .collect()
+    };
+    if threads <= 1 || selected.len() <= 1 {
+        return expand(0, selected);
+    }
+    let chunk_size = selected.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = selected
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(i, chunk)| scope.spawn(move || expand(i * chunk_size, chunk)))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    })
+}
 
-                let legal_actions = now_state.legalActions();
-                for &action in &legal_actions {
-                    let mut next_state = now_state.clone();
-                    next_state.advance(action);
-                    next_state.evaluateScore();
-                    if t == 0 {
-                        next_state.first_action_ = action as isize;
+// pushes the children of one selected state the way expandPlanNode does
+fn pushPlanChildren<S: SinglePlayerState>(
+    now: &PlanNode<S>,
+    children: Vec<(S::Action, S)>,
+    next_beam: &mut BinaryHeap<PlanNode<S>>,
+    seen: &mut FxHashSet<u64>,
+    history: &mut ActionHistory<S::Action>,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> usize {
+    let nodes = children.len();
+    stats.nodes_expanded += nodes;
+    for (action, next_state) in children {
+        if options.dedup && !seen.insert(next_state.zobristHash()) {
+            stats.duplicates_pruned += 1;
+            continue;
+        }
+        next_beam.push(PlanNode {
+            state: next_state,
+            node: Some(history.push(now.node, action)),
+        });
+    }
+    nodes
+}
+
+// pops the states that make up one beam layer, honouring BeamOptions::max_per_region
+fn selectBeam<S: SinglePlayerState + BoardPosition>(
+    now_beam: &mut BinaryHeap<PlanNode<S>>,
+    beam_width: usize,
+    depth: usize,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> Vec<PlanNode<S>> {
+    let mut region_cap = RegionCap::new(options);
+    let mut selected = vec![];
+    while selected.len() < beam_width {
+        let now = match now_beam.pop() {
+            Some(now) => now,
+            None => break,
+        };
+        if region_cap.admit(now.state.characterCoord()) {
+            selected.push(now);
+        }
+    }
+    stats.recordDiversityPruned(depth, region_cap.dropped);
+    selected
+}
+
+// beamSearchPlan with the children of each layer generated on `threads` threads
+// children are merged in the order beamSearchPlan pushes them, so every thread count gives its plan
+fn parallelBeamSearchPlan<S, E>(
+    state: &S,
+    beam_width: usize,
+    beam_depth: usize,
+    threads: usize,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> (Vec<S::Action>, usize)
+where
+    S: SinglePlayerState + BoardPosition + Send + Sync,
+    S::Action: Send,
+    E: Evaluator<S> + Sync,
+{
+    let mut history = ActionHistory::new();
+    let mut now_beam = BinaryHeap::new();
+    let mut best = PlanNode::root(state);
+    now_beam.push(best.clone());
+
+    for t in 0..beam_depth {
+        let selected = selectBeam(&mut now_beam, beam_width, t, options, stats);
+        let expanded = parallelExpand(&selected, threads, None, evaluator);
+        let mut next_beam = BinaryHeap::new();
+        let mut seen = FxHashSet::default();
+        for (now, children) in selected.iter().zip(expanded) {
+            pushPlanChildren(
+                now,
+                children.unwrap(),
+                &mut next_beam,
+                &mut seen,
+                &mut history,
+                options,
+                stats,
+            );
+        }
+
+        now_beam = next_beam;
+        best = now_beam.peek().unwrap().clone();
+        if best.state.isDone() {
+            break;
+        }
+    }
+    (history.plan(best.node), best.state.gameScore())
+}
+
+fn parallelBeamSearchAction<S, E>(
+    state: &S,
+    beam_width: usize,
+    beam_depth: usize,
+    threads: usize,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> S::Action
+where
+    S: SinglePlayerState + BoardPosition + Send + Sync,
+    S::Action: Send,
+    E: Evaluator<S> + Sync,
+{
+    parallelBeamSearchPlan(
+        state, beam_width, beam_depth, threads, evaluator, options, stats,
+    )
+    .0[0]
+}
+
+// beamSearchPlanWithTimeThreshold with the children of each layer generated on `threads` threads
+// node and iteration budgets give the plan of the single-threaded search for every thread count
+fn parallelBeamSearchPlanWithTimeThreshold<S, E>(
+    state: &S,
+    beam_width: usize,
+    budget: SearchBudget,
+    threads: usize,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> (Vec<S::Action>, usize)
+where
+    S: SinglePlayerState + BoardPosition + Send + Sync,
+    S::Action: Send,
+    E: Evaluator<S> + Sync,
+{
+    let mut history = ActionHistory::new();
+    let mut now_beam = BinaryHeap::new();
+    let mut best = PlanNode::root(state);
+    now_beam.push(best.clone());
+    let mut budget_keeper = BudgetKeeper::new(budget);
+    let mut t = 0;
+
+    'search: loop {
+        let selected = selectBeam(&mut now_beam, beam_width, t, options, stats);
+        let expanded = parallelExpand(
+            &selected,
+            threads,
+            Some(&budget_keeper.time_keeper),
+            evaluator,
+        );
+        let mut next_beam = BinaryHeap::new();
+        let mut seen = FxHashSet::default();
+        for (now, children) in selected.iter().zip(expanded) {
+            // a worker only gives up once the deadline has passed
+            if let Some(children) = children {
+                budget_keeper.addNodes(pushPlanChildren(
+                    now,
+                    children,
+                    &mut next_beam,
+                    &mut seen,
+                    &mut history,
+                    options,
+                    stats,
+                ));
+            }
+            // out of budget in the middle of a layer: keep the better of the last full layer and this part
+            if budget_keeper.isOver() {
+                if let Some(partial_best) = next_beam.peek() {
+                    if best.node.is_none() || *partial_best > best {
+                        best = partial_best.clone();
                     }
-                    beam[t + 1].push(next_state);
                 }
+                break 'search;
             }
         }
-        if time_keeper.isTimeOver() {
+        budget_keeper.addIteration();
+        t += 1;
+
+        now_beam = next_beam;
+        best = now_beam.peek().unwrap().clone();
+        if best.state.isDone() || budget_keeper.isOver() {
             break;
         }
     }
-    for t in (0..=beam_depth).rev() {
-        let now_beam = &beam[t];
-        if !now_beam.is_empty() {
-            return now_beam.peek().unwrap().first_action_;
+    stats.recordOvershoot(&budget_keeper);
+    (history.plan(best.node), best.state.gameScore())
+}
+
+fn parallelBeamSearchActionWithTimeThreshold<S, E>(
+    state: &S,
+    beam_width: usize,
+    budget: SearchBudget,
+    threads: usize,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> S::Action
+where
+    S: SinglePlayerState + BoardPosition + Send + Sync,
+    S::Action: Send,
+    E: Evaluator<S> + Sync,
+{
+    parallelBeamSearchPlanWithTimeThreshold(
+        state, beam_width, budget, threads, evaluator, options, stats,
+    )
+    .0[0]
+}
+
+// node of the action tree kept by treeBeamSearchPlan, the states themselves are never stored
+struct TreeBeamNode<A> {
+    parent: usize,
+    // None at the root
+    action: Option<A>,
+    children: Vec<usize>,
+    // order in which this leaf was popped from the beam
+    rank: usize,
+}
+
+// child of a beam leaf that is waiting for selection, ordered by evaluated score alone
+#[derive(Debug, Clone)]
+struct TreeBeamCandidate<A> {
+    parent: usize,
+    action: A,
+    evaluated_score: ScoreType,
+    game_score: usize,
+    hash: u64,
+    is_done: bool,
+    character: Coord,
+    // (rank of the parent, index in its legal actions), the push order of beamSearchPlan
+    order: (usize, usize),
+}
+
+impl<A> std::cmp::PartialEq for TreeBeamCandidate<A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.evaluated_score == other.evaluated_score
+    }
+}
+
+impl<A> std::cmp::Eq for TreeBeamCandidate<A> {}
+
+impl<A> std::cmp::PartialOrd for TreeBeamCandidate<A> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A> std::cmp::Ord for TreeBeamCandidate<A> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.evaluated_score.cmp(&other.evaluated_score)
+    }
+}
+
+// walks the tree from `node` down to the leaves `depth_left` levels below, applying and undoing the
+// actions on the way, and collects the children of every leaf
+fn treeBeamExpand<S: SinglePlayerState + BoardPosition, E: Evaluator<S>>(
+    state: &mut S,
+    nodes: &[TreeBeamNode<S::Action>],
+    node: usize,
+    depth_left: usize,
+    evaluator: &E,
+    candidates: &mut Vec<TreeBeamCandidate<S::Action>>,
+) {
+    if depth_left == 0 {
+        for (i, action) in state.legalActions().into_iter().enumerate() {
+            let undo = state.advance(action);
+            evaluator.evaluate(state);
+            candidates.push(TreeBeamCandidate {
+                parent: node,
+                action,
+                evaluated_score: state.evaluatedScore(),
+                game_score: state.gameScore(),
+                hash: state.zobristHash(),
+                is_done: state.isDone(),
+                character: state.characterCoord(),
+                order: (nodes[node].rank, i),
+            });
+            state.undo(undo);
+        }
+        return;
+    }
+    for &child in &nodes[node].children {
+        let undo = state.advance(nodes[child].action.unwrap());
+        treeBeamExpand(state, nodes, child, depth_left - 1, evaluator, candidates);
+        state.undo(undo);
+    }
+}
+
+// beamSearchPlan that keeps one mutable state instead of a clone per beam entry
+// children are pushed in the same order as beamSearchPlan, so both select the same plan
+fn treeBeamSearchPlan<S: SinglePlayerState + BoardPosition, E: Evaluator<S>>(
+    state: &S,
+    beam_width: usize,
+    beam_depth: usize,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> (Vec<S::Action>, usize) {
+    const ROOT: usize = 0;
+    let mut nodes = vec![TreeBeamNode {
+        parent: ROOT,
+        action: None,
+        children: vec![],
+        rank: 0,
+    }];
+    let mut leaves = vec![ROOT];
+    let mut best_leaf = ROOT;
+    let mut best_score = state.gameScore();
+    let mut best_is_done = false;
+    let mut now_state = state.clone();
+
+    for t in 0..beam_depth {
+        let mut candidates = vec![];
+        treeBeamExpand(&mut now_state, &nodes, ROOT, t, evaluator, &mut candidates);
+        candidates.sort_by_key(|candidate| candidate.order);
+        stats.nodes_expanded += candidates.len();
+
+        let mut next_beam = BinaryHeap::new();
+        let mut seen = FxHashSet::default();
+        for candidate in candidates {
+            if options.dedup && !seen.insert(candidate.hash) {
+                stats.duplicates_pruned += 1;
+                continue;
+            }
+            next_beam.push(candidate);
+        }
+        if let Some(best) = next_beam.peek() {
+            best_score = best.game_score;
+            best_is_done = best.is_done;
+        }
+
+        // like beamSearchPlan, the cap only applies to a layer that is expanded again
+        let last_layer = t + 1 == beam_depth || best_is_done;
+        let mut next_leaves = vec![];
+        let mut region_cap = RegionCap::new(options);
+        while next_leaves.len() < beam_width {
+            let candidate = match next_beam.pop() {
+                Some(candidate) => candidate,
+                None => break,
+            };
+            if !last_layer && !region_cap.admit(candidate.character) {
+                continue;
+            }
+            nodes.push(TreeBeamNode {
+                parent: candidate.parent,
+                action: Some(candidate.action),
+                children: vec![],
+                rank: next_leaves.len(),
+            });
+            let id = nodes.len() - 1;
+            nodes[candidate.parent].children.push(id);
+            next_leaves.push(id);
+        }
+        stats.recordDiversityPruned(t + 1, region_cap.dropped);
+        // cut the branches that lost all of their leaves
+        for &leaf in &leaves {
+            let mut node = leaf;
+            while node != ROOT && nodes[node].children.is_empty() {
+                let parent = nodes[node].parent;
+                nodes[parent].children.retain(|&child| child != node);
+                node = parent;
+            }
+        }
+
+        leaves = next_leaves;
+        best_leaf = leaves[0];
+        if best_is_done {
+            break;
         }
     }
-    -1
+
+    let mut plan = vec![];
+    let mut node = best_leaf;
+    while node != ROOT {
+        plan.push(nodes[node].action.unwrap());
+        node = nodes[node].parent;
+    }
+    plan.reverse();
+    (plan, best_score)
 }
 
-fn playGame() -> usize {
-    let mut state = MazeState::new();
-    // [ms]
-    let time_threshold = 10.0;
-    // state.toString();
-    while !state.isDone() {
-        // state.advance(randomAction(&state));
-        // state.advance(greedyAction(&state));
-        // (state, beam_width, beam_depth)
-        // state.advance(beamSearchAction(&state, 5, 3));
-        // (state, beam_width, time_threshold[s])
-        // state.advance(beamSearchActionWithTimeThreshold(&state, 5, time_threshold * 1e-3));
-        // (state, beam_width, beam_depth, beam_number)
-        // state.advance(chokudaiSearchAction(&state, 1, 3, 1) as usize);
-        // (state, beam_width,beam_depth, time_threshold[s])
-        state.advance(chokudaiSearchActionWithTimeThreshold(&state, 1, END_TURN, time_threshold * 1e-3) as usize);
-        // state.toString();
+fn treeBeamSearchAction<S: SinglePlayerState + BoardPosition, E: Evaluator<S>>(
+    state: &S,
+    beam_width: usize,
+    beam_depth: usize,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> S::Action {
+    treeBeamSearchPlan(state, beam_width, beam_depth, evaluator, options, stats).0[0]
+}
+
+// one sweep of chokudai search: up to beam_width states are taken from every depth
+// the sweep is cut short as soon as the budget runs out
+// returns the number of children generated
+#[allow(clippy::too_many_arguments)]
+fn chokudaiSweep<S: SinglePlayerState, E: Evaluator<S>>(
+    beam: &mut [BinaryHeap<PlanNode<S>>],
+    seen: &mut [FxHashSet<u64>],
+    history: &mut ActionHistory<S::Action>,
+    beam_width: usize,
+    budget_keeper: &mut BudgetKeeper,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> usize {
+    let mut nodes = 0;
+    for t in 0..beam.len() - 1 {
+        for _ in 0..beam_width {
+            if beam[t].is_empty() {
+                break;
+            }
+            if beam[t].peek().unwrap().state.isDone() {
+                break;
+            }
+            let now = beam[t].pop().unwrap();
+            let (_, deeper) = beam.split_at_mut(t + 1);
+            let children = expandPlanNode(
+                &now,
+                &mut deeper[0],
+                &mut seen[t + 1],
+                history,
+                evaluator,
+                options,
+                stats,
+            );
+            budget_keeper.addNodes(children);
+            nodes += children;
+            if budget_keeper.isOver() {
+                return nodes;
+            }
+        }
     }
-    state.game_score_
+    nodes
 }
 
-fn testApiScore(game_number: usize) {
-    let mut score_mean = 0.0;
-    for _ in 0..game_number {
-        score_mean += playGame() as f64;
+// the plan of the best state in the deepest non-empty beam
+fn chokudaiBestPlan<S: SinglePlayerState>(
+    beam: &[BinaryHeap<PlanNode<S>>],
+    history: &ActionHistory<S::Action>,
+) -> (Vec<S::Action>, usize) {
+    for now_beam in beam.iter().rev() {
+        if let Some(best) = now_beam.peek() {
+            return (history.plan(best.node), best.state.gameScore());
+        }
     }
-    score_mean /= game_number as f64;
-    println!("Score: {:.2}", score_mean);
+    (vec![], 0)
 }
 
-fn main() {
-    let start = Instant::now();
-    testApiScore(100);
-    println!("Elapsed time: {}sec", start.elapsed().as_secs());
+fn chokudaiSearchPlan<S: SinglePlayerState, E: Evaluator<S>>(
+    state: &S,
+    beam_width: usize,
+    beam_depth: usize,
+    beam_number: usize,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> (Vec<S::Action>, usize) {
+    let mut history = ActionHistory::new();
+    let mut beam = vec![BinaryHeap::new(); beam_depth + 1];
+    let mut seen = vec![FxHashSet::default(); beam_depth + 1];
+    beam[0].push(PlanNode::root(state));
+    let mut budget_keeper = BudgetKeeper::new(SearchBudget::iterations(beam_number));
+
+    while !budget_keeper.isOver() {
+        chokudaiSweep(
+            &mut beam,
+            &mut seen,
+            &mut history,
+            beam_width,
+            &mut budget_keeper,
+            evaluator,
+            options,
+            stats,
+        );
+        budget_keeper.addIteration();
+    }
+    chokudaiBestPlan(&beam, &history)
+}
+
+fn chokudaiSearchAction<S: SinglePlayerState, E: Evaluator<S>>(
+    state: &S,
+    beam_width: usize,
+    beam_depth: usize,
+    beam_number: usize,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> Option<S::Action> {
+    chokudaiSearchPlan(
+        state,
+        beam_width,
+        beam_depth,
+        beam_number,
+        evaluator,
+        options,
+        stats,
+    )
+    .0
+    .first()
+    .copied()
+}
+
+fn chokudaiSearchPlanWithTimeThreshold<S: SinglePlayerState, E: Evaluator<S>>(
+    state: &S,
+    beam_width: usize,
+    beam_depth: usize,
+    budget: SearchBudget,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> (Vec<S::Action>, usize) {
+    let mut history = ActionHistory::new();
+    let mut beam = vec![BinaryHeap::new(); beam_depth + 1];
+    let mut seen = vec![FxHashSet::default(); beam_depth + 1];
+    beam[0].push(PlanNode::root(state));
+    let mut budget_keeper = BudgetKeeper::new(budget);
+
+    loop {
+        let nodes = chokudaiSweep(
+            &mut beam,
+            &mut seen,
+            &mut history,
+            beam_width,
+            &mut budget_keeper,
+            evaluator,
+            options,
+            stats,
+        );
+        budget_keeper.addIteration();
+        // nothing left to expand, a node budget would never run out
+        if nodes == 0 || budget_keeper.isOver() {
+            break;
+        }
+    }
+    stats.recordOvershoot(&budget_keeper);
+    chokudaiBestPlan(&beam, &history)
+}
+
+fn chokudaiSearchActionWithTimeThreshold<S: SinglePlayerState, E: Evaluator<S>>(
+    state: &S,
+    beam_width: usize,
+    beam_depth: usize,
+    budget: SearchBudget,
+    evaluator: &E,
+    options: &BeamOptions,
+    stats: &mut SearchStats,
+) -> Option<S::Action> {
+    chokudaiSearchPlanWithTimeThreshold(
+        state, beam_width, beam_depth, budget, evaluator, options, stats,
+    )
+    .0
+    .first()
+    .copied()
+}
+
+// plays random moves until the end of the game, returns the number of moves played
+fn playout<S: SinglePlayerState, R: Rng>(state: &mut S, rng: &mut R) -> usize {
+    let mut nodes = 0;
+    while !state.isDone() {
+        let legal_actions = state.legalActions();
+        if legal_actions.is_empty() {
+            break;
+        }
+        state.advance(legal_actions[rng.gen_range(0, legal_actions.len())]);
+        nodes += 1;
+    }
+    nodes
+}
+
+// the first move whose random playouts end with the best mean game score
+// one iteration runs one playout after every legal first move
+fn primitiveMontecarloAction<S: SinglePlayerState, R: Rng>(
+    state: &S,
+    budget: SearchBudget,
+    rng: &mut R,
+    stats: &mut SearchStats,
+) -> S::Action {
+    let legal_actions = state.legalActions();
+    // every move gets the same number of playouts, so the sums order like the means
+    let mut score_sums = vec![0; legal_actions.len()];
+    let mut budget_keeper = BudgetKeeper::new(budget);
+
+    loop {
+        for (i, &action) in legal_actions.iter().enumerate() {
+            let mut next_state = state.clone();
+            next_state.advance(action);
+            let nodes = 1 + playout(&mut next_state, rng);
+            budget_keeper.addNodes(nodes);
+            stats.nodes_expanded += nodes;
+            score_sums[i] += next_state.gameScore();
+        }
+        budget_keeper.addIteration();
+        if budget_keeper.isOver() {
+            break;
+        }
+    }
+    stats.recordOvershoot(&budget_keeper);
+
+    let mut best_index = 0;
+    for i in 1..legal_actions.len() {
+        if score_sums[i] > score_sums[best_index] {
+            best_index = i;
+        }
+    }
+    legal_actions[best_index]
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum PlayoutPolicy {
+    Random,
+    Greedy,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Backup {
+    // a node is worth the best playout seen below it
+    Max,
+    // a node is worth the average of the playouts below it
+    Mean,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MctsOptions {
+    // weight of the UCB1 exploration term, node values are scaled to 0..=1 by the best playout so far
+    exploration: f64,
+    // a leaf gets children once it has been visited this many times
+    expand_threshold: usize,
+    playout: PlayoutPolicy,
+    backup: Backup,
+}
+
+impl Default for MctsOptions {
+    fn default() -> Self {
+        MctsOptions {
+            exploration: 1.0,
+            expand_threshold: 10,
+            playout: PlayoutPolicy::Random,
+            backup: Backup::Mean,
+        }
+    }
+}
+
+struct MctsNode<S: SinglePlayerState> {
+    state: S,
+    action: Option<S::Action>,
+    children: Vec<usize>,
+    visits: usize,
+    score_sum: usize,
+    best_score: usize,
+}
+
+impl<S: SinglePlayerState> MctsNode<S> {
+    fn new(state: S, action: Option<S::Action>) -> Self {
+        MctsNode {
+            state,
+            action,
+            children: vec![],
+            visits: 0,
+            score_sum: 0,
+            best_score: 0,
+        }
+    }
+
+    fn value(&self, backup: Backup) -> f64 {
+        match backup {
+            Backup::Max => self.best_score as f64,
+            Backup::Mean => self.score_sum as f64 / self.visits as f64,
+        }
+    }
+}
+
+// plays the policy until the end of the game, returns the number of moves played
+fn policyPlayout<S: SinglePlayerState, R: Rng>(state: &mut S, policy: PlayoutPolicy, rng: &mut R) -> usize {
+    match policy {
+        PlayoutPolicy::Random => playout(state, rng),
+        PlayoutPolicy::Greedy => {
+            let mut nodes = 0;
+            while !state.isDone() && !state.legalActions().is_empty() {
+                state.advance(greedyAction(state));
+                nodes += 1;
+            }
+            nodes
+        }
+    }
+}
+
+// UCB1 over the children of a visited node, unvisited children first
+fn mctsSelect<S: SinglePlayerState>(tree: &[MctsNode<S>], node: usize, options: &MctsOptions) -> usize {
+    let parent = &tree[node];
+    let scale = tree[0].best_score.max(1) as f64;
+    let log_visits = (parent.visits as f64).ln();
+    let mut best_child = parent.children[0];
+    let mut best_ucb = f64::NEG_INFINITY;
+    for &child in &parent.children {
+        let child_node = &tree[child];
+        if child_node.visits == 0 {
+            return child;
+        }
+        let ucb = child_node.value(options.backup) / scale
+            + options.exploration * (log_visits / child_node.visits as f64).sqrt();
+        if ucb > best_ucb {
+            best_ucb = ucb;
+            best_child = child;
+        }
+    }
+    best_child
+}
+
+// the most visited first move after the search, one iteration is one playout
+fn mctsAction<S: SinglePlayerState, R: Rng>(
+    state: &S,
+    budget: SearchBudget,
+    options: &MctsOptions,
+    rng: &mut R,
+    stats: &mut SearchStats,
+) -> S::Action {
+    let mut tree = vec![MctsNode::new(state.clone(), None)];
+    let mut budget_keeper = BudgetKeeper::new(budget);
+    let mut path = vec![];
+
+    loop {
+        path.clear();
+        path.push(0);
+        let mut node = 0;
+        // the root is always expanded so that every first move gets a playout
+        loop {
+            if tree[node].children.is_empty()
+                && (node == 0 || tree[node].visits >= options.expand_threshold)
+                && !tree[node].state.isDone()
+            {
+                for action in tree[node].state.legalActions() {
+                    let mut next_state = tree[node].state.clone();
+                    next_state.advance(action);
+                    budget_keeper.addNodes(1);
+                    stats.nodes_expanded += 1;
+                    let child = tree.len();
+                    tree.push(MctsNode::new(next_state, Some(action)));
+                    tree[node].children.push(child);
+                }
+            }
+            if tree[node].children.is_empty() {
+                break;
+            }
+            node = mctsSelect(&tree, node, options);
+            path.push(node);
+        }
+
+        let mut playout_state = tree[node].state.clone();
+        let nodes = policyPlayout(&mut playout_state, options.playout, rng);
+        // a playout from a finished leaf still costs a node, or a node budget never runs out near the end
+        budget_keeper.addNodes(nodes.max(1));
+        stats.nodes_expanded += nodes;
+        let score = playout_state.gameScore();
+        for &node in &path {
+            let tree_node = &mut tree[node];
+            tree_node.visits += 1;
+            tree_node.score_sum += score;
+            tree_node.best_score = tree_node.best_score.max(score);
+        }
+
+        budget_keeper.addIteration();
+        if budget_keeper.isOver() {
+            break;
+        }
+    }
+    stats.recordOvershoot(&budget_keeper);
+
+    let best_child = *tree[0]
+        .children
+        .iter()
+        .max_by(|&&a, &&b| {
+            (tree[a].visits, tree[a].best_score)
+                .cmp(&(tree[b].visits, tree[b].best_score))
+                .then(b.cmp(&a))
+        })
+        .unwrap();
+    tree[best_child].action.unwrap()
+}
+
+// memoized DFS over (turn, character cell, eaten point cells), only meant for small boards
+struct ExactSolver {
+    // bit of every cell that holds a point at the start, None for empty cells
+    point_bits: Vec<Option<u32>>,
+    // best score still to be gained from a key, and the move that gains it
+    memo: FxHashMap<(usize, usize, u64), (usize, usize)>,
+}
+
+impl ExactSolver {
+    fn new<S: MazeGame>(state: &S) -> Self {
+        let points = &state.grid().points_;
+        let mut point_bits = vec![None; points.len()];
+        let mut bits = 0;
+        for (cell, &point) in points.iter().enumerate() {
+            if point > 0 {
+                point_bits[cell] = Some(bits);
+                bits += 1;
+            }
+        }
+        assert!(
+            bits <= 64,
+            "exact solver supports at most 64 cells with points"
+        );
+        ExactSolver {
+            point_bits,
+            memo: FxHashMap::default(),
+        }
+    }
+
+    fn cell<S: MazeGame>(state: &S) -> usize {
+        let grid = state.grid();
+        grid.character_.y_ as usize * grid.config_.w + grid.character_.x_ as usize
+    }
+
+    fn search<S: MazeGame>(&mut self, state: &mut S, eaten: u64, stats: &mut SearchStats) -> usize {
+        if state.isDone() {
+            return 0;
+        }
+        let key = (state.grid().turn_, ExactSolver::cell(state), eaten);
+        if let Some(&(best_score, _)) = self.memo.get(&key) {
+            return best_score;
+        }
+        let mut best_score = 0;
+        let mut best_action = None;
+        for action in state.legalActions() {
+            let undo = state.advance(action);
+            stats.nodes_expanded += 1;
+            let next_eaten = match self.point_bits[ExactSolver::cell(state)] {
+                Some(bit) if undo.point > 0 => eaten | 1 << bit,
+                _ => eaten,
+            };
+            let score = undo.point + self.search(state, next_eaten, stats);
+            state.undo(undo);
+            if best_action.is_none() || score > best_score {
+                best_score = score;
+                best_action = Some(action);
+            }
+        }
+        // a character with no legal move stays put until the game ends
+        if let Some(best_action) = best_action {
+            self.memo.insert(key, (best_score, best_action));
+        }
+        best_score
+    }
+}
+
+// optimal action sequence from `state` and the game score it reaches
+fn exactSolvePlan<S: MazeGame>(state: &S, stats: &mut SearchStats) -> (Vec<usize>, usize) {
+    let mut solver = ExactSolver::new(state);
+    let mut now_state = state.clone();
+    let best_score = state.gameScore() + solver.search(&mut now_state, 0, stats);
+
+    let mut plan = vec![];
+    let mut eaten = 0;
+    while let Some(&(_, action)) = solver
+        .memo
+        .get(&(now_state.grid().turn_, ExactSolver::cell(&now_state), eaten))
+    {
+        let undo = now_state.advance(action);
+        if let Some(bit) = solver.point_bits[ExactSolver::cell(&now_state)] {
+            if undo.point > 0 {
+                eaten |= 1 << bit;
+            }
+        }
+        plan.push(action);
+    }
+    debug_assert_eq!(now_state.gameScore(), best_score);
+    (plan, best_score)
+}
+
+// best-first search on game score plus MazeGrid::remainingPointBound, the bound never underestimates
+// the first finished state taken from the heap is optimal; when the budget runs out first, the plan
+// of the highest-scoring state generated so far is returned instead
+fn aStarSearchPlan<S: MazeGame>(state: &S, budget: SearchBudget, stats: &mut SearchStats) -> (Vec<usize>, usize) {
+    let mut history = ActionHistory::new();
+    let mut open = BinaryHeap::new();
+    // states with the same turn and hash have eaten the same points, so they are interchangeable
+    let mut seen = FxHashSet::default();
+    let mut root = PlanNode::root(state);
+    let bound = |state: &S| (state.gameScore() + state.grid().remainingPointBound()) as ScoreType;
+    root.state.setEvaluatedScore(bound(&root.state));
+    seen.insert((root.state.grid().turn_, root.state.zobristHash()));
+    let mut incumbent = root.clone();
+    open.push(root);
+    let mut budget_keeper = BudgetKeeper::new(budget);
+
+    while let Some(now) = open.pop() {
+        if now.state.isDone() {
+            incumbent = now;
+            break;
+        }
+        let legal_actions = now.state.legalActions();
+        stats.nodes_expanded += legal_actions.len();
+        budget_keeper.addNodes(legal_actions.len());
+        for action in legal_actions {
+            let mut next_state = now.state.clone();
+            next_state.advance(action);
+            if !seen.insert((next_state.grid().turn_, next_state.zobristHash())) {
+                stats.duplicates_pruned += 1;
+                continue;
+            }
+            next_state.setEvaluatedScore(bound(&next_state));
+            let next = PlanNode {
+                state: next_state,
+                node: Some(history.push(now.node, action)),
+            };
+            if next.state.gameScore() > incumbent.state.gameScore() || incumbent.node.is_none() {
+                incumbent = next.clone();
+            }
+            open.push(next);
+        }
+        budget_keeper.addIteration();
+        if budget_keeper.isOver() {
+            break;
+        }
+    }
+    stats.recordOvershoot(&budget_keeper);
+    (history.plan(incumbent.node), incumbent.state.gameScore())
+}
+
+fn aStarSearchAction<S: MazeGame>(state: &S, budget: SearchBudget, stats: &mut SearchStats) -> usize {
+    aStarSearchPlan(state, budget, stats).0[0]
+}
+
+// per-game resources handed to the AI on every search
+struct GameContext {
+    // seeded from the game seed, on a different stream than the board
+    rng: rand::rngs::StdRng,
+    stats: SearchStats,
+}
+
+impl GameContext {
+    fn new(seed: u64) -> Self {
+        GameContext {
+            rng: rand::SeedableRng::seed_from_u64(!seed),
+            stats: SearchStats::default(),
+        }
+    }
+}
+
+// returns the actions to play from the given state, at least one
+// mutable search state lives in the GameContext, so one handler can play games on several threads at once
+type Handler<S> = Box<dyn Fn(&S, &mut GameContext) -> Vec<usize> + Sync>;
+
+fn playGame<S: MazeGame>(
+    ai: &(&str, Handler<S>),
+    config: &MazeConfig,
+    seed: u64,
+    replan_interval: usize,
+) -> (usize, SearchStats) {
+    playState(ai, S::fromSeed(*config, seed), seed, replan_interval)
+}
+
+// up to replan_interval actions of each plan are played before searching again
+// replan_interval = 1 searches every turn
+fn playState<S: MazeGame>(
+    ai: &(&str, Handler<S>),
+    mut state: S,
+    seed: u64,
+    replan_interval: usize,
+) -> (usize, SearchStats) {
+    let mut context = GameContext::new(seed);
+    let mut plan = vec![];
+    let mut plan_index = 0;
+    // state.toString();
+    while !state.isDone() {
+        if plan_index == plan.len() || plan_index == replan_interval {
+            plan = ai.1(&state, &mut context);
+            plan_index = 0;
+        }
+        state.advance(plan[plan_index]);
+        plan_index += 1;
+        // state.toString();
+    }
+    (state.gameScore(), context.stats)
+}
+
+// calls play(i) for every game i in 0..game_number on `jobs` worker threads
+// results come back in game order, so they never depend on the thread count or scheduling
+fn runGames<T: Send, F: Fn(usize) -> T + Sync>(game_number: usize, jobs: usize, play: F) -> Vec<T> {
+    let next_game = std::sync::atomic::AtomicUsize::new(0);
+    let mut results: Vec<(usize, T)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.clamp(1, game_number.max(1)))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let game = next_game.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        if game >= game_number {
+                            break;
+                        }
+                        results.push((game, play(game)));
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    results.sort_by_key(|&(game, _)| game);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+fn printSearchStats(stats: &SearchStats) {
+    println!("Nodes expanded: {}", stats.nodes_expanded);
+    println!("Pruned duplicates: {}", stats.duplicates_pruned);
+    println!(
+        "Max deadline overshoot: {:.3}ms",
+        stats.max_deadline_overshoot * 1e3
+    );
+    if !stats.diversity_pruned.is_empty() {
+        println!("Diversity pruned per depth: {:?}", stats.diversity_pruned);
+    }
+}
+
+// summary statistics of a sample, e.g. the scores of a series of games
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Distribution {
+    n: usize,
+    mean: f64,
+    // sample standard deviation, 0 for fewer than two values
+    std_dev: f64,
+    min: f64,
+    p5: f64,
+    median: f64,
+    p95: f64,
+    max: f64,
+    // 95% confidence interval of the mean, normal approximation
+    ci_low: f64,
+    ci_high: f64,
+}
+
+impl Distribution {
+    fn new(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Distribution::default();
+        }
+        let n = values.len();
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        // linear interpolation between the closest ranks
+        let percentile = |p: f64| -> f64 {
+            let rank = p * (n - 1) as f64;
+            let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+            sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+        };
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let std_dev = if n < 2 {
+            0.0
+        } else {
+            (values
+                .iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1) as f64)
+                .sqrt()
+        };
+        let half_width = 1.96 * std_dev / (n as f64).sqrt();
+        Distribution {
+            n,
+            mean,
+            std_dev,
+            min: sorted[0],
+            p5: percentile(0.05),
+            median: percentile(0.5),
+            p95: percentile(0.95),
+            max: sorted[n - 1],
+            ci_low: mean - half_width,
+            ci_high: mean + half_width,
+        }
+    }
+
+    // values scaled by `scale`, e.g. 1e3 for seconds to milliseconds
+    fn scaled(&self, scale: f64) -> Self {
+        Distribution {
+            n: self.n,
+            mean: self.mean * scale,
+            std_dev: self.std_dev * scale,
+            min: self.min * scale,
+            p5: self.p5 * scale,
+            median: self.median * scale,
+            p95: self.p95 * scale,
+            max: self.max * scale,
+            ci_low: self.ci_low * scale,
+            ci_high: self.ci_high * scale,
+        }
+    }
+}
+
+impl std::fmt::Display for Distribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "mean {:.2}, std dev {:.2}, min {:.2}, p5 {:.2}, median {:.2}, p95 {:.2}, max {:.2}, 95% CI [{:.2}, {:.2}]",
+            self.mean, self.std_dev, self.min, self.p5, self.median, self.p95, self.max, self.ci_low, self.ci_high
+        )
+    }
+}
+
+// comparison of two series of games played on the same boards, game i of one against game i of the other
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct PairedComparison {
+    // games where the first series scored higher, the same, lower
+    wins: usize,
+    ties: usize,
+    losses: usize,
+    // first minus second, per game
+    difference: Distribution,
+    // two-sided Wilcoxon signed-rank test, normal approximation with tie and continuity correction
+    p_value: f64,
+}
+
+impl PairedComparison {
+    fn new(first: &[f64], second: &[f64]) -> Self {
+        assert_eq!(
+            first.len(),
+            second.len(),
+            "paired series must have the same length"
+        );
+        let differences: Vec<f64> = first.iter().zip(second).map(|(a, b)| a - b).collect();
+        let mut nonzero: Vec<f64> = differences.iter().copied().filter(|&d| d != 0.0).collect();
+        nonzero.sort_by(|a, b| a.abs().partial_cmp(&b.abs()).unwrap());
+
+        // ranks of |d| starting from 1, tied values share their average rank
+        let n = nonzero.len();
+        let mut rank_sum_positive = 0.0;
+        let mut tie_correction = 0.0;
+        let mut i = 0;
+        while i < n {
+            let mut j = i;
+            while j + 1 < n && nonzero[j + 1].abs() == nonzero[i].abs() {
+                j += 1;
+            }
+            let rank = (i + j + 2) as f64 / 2.0;
+            let tied = (j - i + 1) as f64;
+            tie_correction += tied * tied * tied - tied;
+            rank_sum_positive += rank * nonzero[i..=j].iter().filter(|&&d| d > 0.0).count() as f64;
+            i = j + 1;
+        }
+        let n = n as f64;
+        let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - tie_correction / 48.0;
+        let p_value = if variance <= 0.0 {
+            1.0
+        } else {
+            let z = ((rank_sum_positive - n * (n + 1.0) / 4.0).abs() - 0.5).max(0.0) / variance.sqrt();
+            libm::erfc(z / std::f64::consts::SQRT_2)
+        };
+
+        PairedComparison {
+            wins: differences.iter().filter(|&&d| d > 0.0).count(),
+            ties: differences.iter().filter(|&&d| d == 0.0).count(),
+            losses: differences.iter().filter(|&&d| d < 0.0).count(),
+            difference: Distribution::new(&differences),
+            p_value,
+        }
+    }
+}
+
+impl std::fmt::Display for PairedComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "win/tie/loss {}/{}/{}, difference mean {:.2} 95% CI [{:.2}, {:.2}], Wilcoxon p = {:.4}",
+            self.wins,
+            self.ties,
+            self.losses,
+            self.difference.mean,
+            self.difference.ci_low,
+            self.difference.ci_high,
+            self.p_value
+        )
+    }
+}
+
+// result of testApiScore for one AI
+#[derive(Debug, Clone)]
+struct ScoreSummary {
+    name: String,
+    // settings the AI was built with, space-separated key=value pairs
+    parameters: String,
+    // seed of every game, and its score, play time [s] and expanded nodes at the same index
+    seeds: Vec<u64>,
+    scores: Vec<usize>,
+    game_times: Vec<f64>,
+    game_nodes: Vec<usize>,
+    stats: SearchStats,
+    // [s]
+    elapsed: f64,
+}
+
+impl ScoreSummary {
+    fn scoreDistribution(&self) -> Distribution {
+        Distribution::new(
+            &self
+                .scores
+                .iter()
+                .map(|&score| score as f64)
+                .collect::<Vec<_>>(),
+        )
+    }
+    // [ms]
+    fn gameTimeDistribution(&self) -> Distribution {
+        Distribution::new(&self.game_times).scaled(1e3)
+    }
+}
+
+// game i is played on the board generated from seeds.start + i, on `jobs` worker threads
+fn testApiScore<S: MazeGame>(
+    ai: &(&str, Handler<S>),
+    config: &MazeConfig,
+    seeds: std::ops::Range<u64>,
+    replan_interval: usize,
+    jobs: usize,
+) -> ScoreSummary {
+    let start = Instant::now();
+    let seeds: Vec<u64> = seeds.collect();
+    let results = runGames(seeds.len(), jobs, |game| {
+        let game_start = Instant::now();
+        let (score, stats) = playGame(ai, config, seeds[game], replan_interval);
+        (score, stats, game_start.elapsed().as_secs_f64())
+    });
+    let mut summary = ScoreSummary {
+        name: ai.0.to_string(),
+        parameters: String::new(),
+        seeds,
+        scores: vec![],
+        game_times: vec![],
+        game_nodes: vec![],
+        stats: SearchStats::default(),
+        elapsed: 0.0,
+    };
+    for (score, stats, game_time) in results {
+        summary.scores.push(score);
+        summary.game_times.push(game_time);
+        summary.game_nodes.push(stats.nodes_expanded);
+        summary.stats.merge(&stats);
+    }
+    summary.elapsed = start.elapsed().as_secs_f64();
+    summary
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum OutputFormat {
+    Text,
+    // one header line, then one line per algorithm
+    Csv,
+    // one object per line
+    Json,
+}
+
+fn printSummaryHeader(format: OutputFormat) {
+    if format == OutputFormat::Csv {
+        println!(
+            "algorithm,parameters,games,score_mean,score_std_dev,score_min,score_p5,score_median,score_p95,score_max,\
+             score_ci_low,score_ci_high,game_time_mean_ms,game_time_p95_ms,game_time_max_ms,\
+             nodes_expanded,duplicates_pruned,max_deadline_overshoot_ms,elapsed_sec"
+        );
+    }
+}
+
+fn printSummary(summary: &ScoreSummary, format: OutputFormat) {
+    let score = summary.scoreDistribution();
+    let game_time = summary.gameTimeDistribution();
+    match format {
+        OutputFormat::Text => {
+            println!("Score of {}: {:.2}", summary.name, score.mean);
+            println!("Parameters: {}", summary.parameters);
+            println!("Score distribution: {}", score);
+            println!("Game time distribution [ms]: {}", game_time);
+            printSearchStats(&summary.stats);
+            println!("Elapsed time: {:.3}sec", summary.elapsed);
+        }
+        OutputFormat::Csv => println!(
+            "{},{},{},{:.2},{:.2},{},{:.2},{:.2},{:.2},{},{:.2},{:.2},{:.3},{:.3},{:.3},{},{},{:.3},{:.3}",
+            summary.name,
+            summary.parameters,
+            score.n,
+            score.mean,
+            score.std_dev,
+            score.min,
+            score.p5,
+            score.median,
+            score.p95,
+            score.max,
+            score.ci_low,
+            score.ci_high,
+            game_time.mean,
+            game_time.p95,
+            game_time.max,
+            summary.stats.nodes_expanded,
+            summary.stats.duplicates_pruned,
+            summary.stats.max_deadline_overshoot * 1e3,
+            summary.elapsed
+        ),
+        OutputFormat::Json => println!(
+            "{{\"algorithm\":\"{}\",\"parameters\":\"{}\",\"games\":{},\"score_mean\":{:.2},\"score_std_dev\":{:.2},\"score_min\":{},\
+             \"score_p5\":{:.2},\"score_median\":{:.2},\"score_p95\":{:.2},\"score_max\":{},\"score_ci_low\":{:.2},\
+             \"score_ci_high\":{:.2},\"game_time_mean_ms\":{:.3},\"game_time_p95_ms\":{:.3},\"game_time_max_ms\":{:.3},\
+             \"nodes_expanded\":{},\"duplicates_pruned\":{},\"max_deadline_overshoot_ms\":{:.3},\"elapsed_sec\":{:.3}}}",
+            summary.name,
+            summary.parameters,
+            score.n,
+            score.mean,
+            score.std_dev,
+            score.min,
+            score.p5,
+            score.median,
+            score.p95,
+            score.max,
+            score.ci_low,
+            score.ci_high,
+            game_time.mean,
+            game_time.p95,
+            game_time.max,
+            summary.stats.nodes_expanded,
+            summary.stats.duplicates_pruned,
+            summary.stats.max_deadline_overshoot * 1e3,
+            summary.elapsed
+        ),
+    }
+}
+
+// summaries from best to worst mean score, all played on the same seeds
+fn printRanking(summaries: &[ScoreSummary]) {
+    let mut ranked: Vec<(&ScoreSummary, Distribution)> = summaries
+        .iter()
+        .map(|summary| (summary, summary.scoreDistribution()))
+        .collect();
+    ranked.sort_by(|a, b| b.1.mean.partial_cmp(&a.1.mean).unwrap());
+    println!("Ranking over {} games:", summaries[0].seeds.len());
+    println!(
+        "{:>4} {:<20} {:>8} {:>19} {:>8} {:>10}  parameters",
+        "rank", "algorithm", "mean", "95% CI", "std dev", "time [ms]"
+    );
+    for (rank, (summary, score)) in ranked.iter().enumerate() {
+        println!(
+            "{:>4} {:<20} {:>8.2} {:>19} {:>8.2} {:>10.3}  {}",
+            rank + 1,
+            summary.name,
+            score.mean,
+            format!("[{:.2}, {:.2}]", score.ci_low, score.ci_high),
+            score.std_dev,
+            summary.gameTimeDistribution().mean,
+            summary.parameters
+        );
+    }
+}
+
+// every pair of summaries, each played on the same seeds
+fn printComparisons(summaries: &[ScoreSummary]) {
+    for (i, first) in summaries.iter().enumerate() {
+        for second in &summaries[i + 1..] {
+            assert_eq!(
+                first.seeds, second.seeds,
+                "compared summaries must share their seeds"
+            );
+            let scores =
+                |summary: &ScoreSummary| -> Vec<f64> { summary.scores.iter().map(|&score| score as f64).collect() };
+            let comparison = PairedComparison::new(&scores(first), &scores(second));
+            println!("{} vs {}: {}", first.name, second.name, comparison);
+            let differences: Vec<String> = first
+                .seeds
+                .iter()
+                .zip(first.scores.iter().zip(&second.scores))
+                .map(|(seed, (&a, &b))| format!("{}:{:+}", seed, a as isize - b as isize))
+                .collect();
+            println!(
+                "Per-seed differences (seed:difference): {}",
+                differences.join(" ")
+            );
+        }
+    }
+}
+
+// one row per game: seed,algorithm,parameters,score,elapsed_ms,nodes_expanded
+fn writeGamesCsv(path: &str, summaries: &[ScoreSummary]) -> std::io::Result<()> {
+    let mut text = String::from("seed,algorithm,parameters,score,elapsed_ms,nodes_expanded\n");
+    for summary in summaries {
+        for game in 0..summary.seeds.len() {
+            text += &format!(
+                "{},{},{},{},{:.3},{}\n",
+                summary.seeds[game],
+                summary.name,
+                summary.parameters,
+                summary.scores[game],
+                summary.game_times[game] * 1e3,
+                summary.game_nodes[game]
+            );
+        }
+    }
+    std::fs::write(path, text)
+}
+
+// the rows of writeGamesCsv as an array of objects
+fn writeGamesJson(path: &str, summaries: &[ScoreSummary]) -> std::io::Result<()> {
+    let mut rows = vec![];
+    for summary in summaries {
+        for game in 0..summary.seeds.len() {
+            rows.push(format!(
+                "  {{\"seed\":{},\"algorithm\":\"{}\",\"parameters\":\"{}\",\"score\":{},\"elapsed_ms\":{:.3},\"nodes_expanded\":{}}}",
+                summary.seeds[game],
+                summary.name,
+                summary.parameters,
+                summary.scores[game],
+                summary.game_times[game] * 1e3,
+                summary.game_nodes[game]
+            ));
+        }
+    }
+    std::fs::write(path, format!("[\n{}\n]\n", rows.join(",\n")))
+}
+
+// one game of a file written by writeGamesCsv
+#[derive(Debug, Clone)]
+struct BaselineGame {
+    seed: u64,
+    algorithm: String,
+    parameters: String,
+    score: f64,
+}
+
+// every game in a file written by writeGamesCsv
+fn readBaseline(path: &str) -> Result<Vec<BaselineGame>, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let mut lines = text.lines().enumerate();
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| format!("{}: empty baseline", path))?;
+    let columns: Vec<&str> = header.split(',').collect();
+    let column = |name: &str| -> Result<usize, String> {
+        columns
+            .iter()
+            .position(|&column| column == name)
+            .ok_or_else(|| format!("{}: no {} column", path, name))
+    };
+    let (seed, algorithm, parameters, score) = (
+        column("seed")?,
+        column("algorithm")?,
+        column("parameters")?,
+        column("score")?,
+    );
+    let mut games = vec![];
+    for (i, line) in lines.filter(|(_, line)| !line.is_empty()) {
+        let fields: Vec<&str> = line.split(',').collect();
+        let invalid = || format!("{}: line {}: invalid row", path, i + 1);
+        if fields.len() != columns.len() {
+            return Err(invalid());
+        }
+        games.push(BaselineGame {
+            seed: fields[seed].parse().map_err(|_| invalid())?,
+            algorithm: fields[algorithm].to_string(),
+            parameters: fields[parameters].to_string(),
+            score: fields[score].parse().map_err(|_| invalid())?,
+        });
+    }
+    Ok(games)
+}
+
+// compares the mean score of every summary with the baseline games of the same algorithm, parameters and seeds
+// returns false if any mean dropped by more than `tolerance` points or has no baseline to compare with
+fn checkBaseline(summaries: &[ScoreSummary], baseline: &[BaselineGame], tolerance: f64) -> bool {
+    let mut passed = true;
+    for summary in summaries {
+        let mut score_sum = 0.0;
+        let mut baseline_sum = 0.0;
+        let mut games = 0;
+        for (game, &seed) in summary.seeds.iter().enumerate() {
+            if let Some(baseline_game) = baseline.iter().find(|baseline_game| {
+                baseline_game.seed == seed
+                    && baseline_game.algorithm == summary.name
+                    && baseline_game.parameters == summary.parameters
+            }) {
+                score_sum += summary.scores[game] as f64;
+                baseline_sum += baseline_game.score;
+                games += 1;
+            }
+        }
+        if games == 0 {
+            println!(
+                "Baseline check of {} ({}): FAILED, no baseline games with these parameters and seeds",
+                summary.name, summary.parameters
+            );
+            passed = false;
+            continue;
+        }
+        let (score_mean, baseline_mean) = (score_sum / games as f64, baseline_sum / games as f64);
+        let ok = score_mean >= baseline_mean - tolerance;
+        println!(
+            "Baseline check of {} ({}): mean {:.2} vs baseline {:.2} over {} games (tolerance {:.2}): {}",
+            summary.name,
+            summary.parameters,
+            score_mean,
+            baseline_mean,
+            games,
+            tolerance,
+            if ok { "ok" } else { "FAILED" }
+        );
+        passed &= ok;
+    }
+    passed
+}
+
+// plays every game on the boards of testApiScore and compares the scores with the exact solver
+// keep the config small, the solver is exponential in the number of turns
+fn testOptimalityGap<S: MazeGame>(
+    ai: &(&str, Handler<S>),
+    config: &MazeConfig,
+    seeds: std::ops::Range<u64>,
+    replan_interval: usize,
+    jobs: usize,
+) {
+    let game_number = seeds.end - seeds.start;
+    let mut gap_mean = 0.0;
+    let mut ratio_mean = 0.0;
+    let mut optimal_games = 0;
+    let results = runGames(game_number as usize, jobs, |game| {
+        let seed = seeds.start + game as u64;
+        let state = S::fromSeed(*config, seed);
+        let (_, optimal_score) = exactSolvePlan(&state, &mut SearchStats::default());
+        let (score, _) = playState(ai, state, seed, replan_interval);
+        (optimal_score, score)
+    });
+    for (optimal_score, score) in results {
+        gap_mean += (optimal_score - score) as f64;
+        ratio_mean += if optimal_score == 0 {
+            1.0
+        } else {
+            score as f64 / optimal_score as f64
+        };
+        if score == optimal_score {
+            optimal_games += 1;
+        }
+    }
+    gap_mean /= game_number as f64;
+    ratio_mean /= game_number as f64;
+    println!(
+        "Optimality gap of {}: {:.2} ({:.2}% of optimal, optimal in {}/{} games)",
+        ai.0,
+        gap_mean,
+        ratio_mean * 100.0,
+        optimal_games,
+        game_number
+    );
+}
+
+const ALGORITHMS: [&str; 12] = [
+    "random",
+    "greedy",
+    "montecarlo",
+    "mcts",
+    "astar",
+    "beam",
+    "beam-time",
+    "parallel-beam",
+    "parallel-beam-time",
+    "tree-beam",
+    "chokudai",
+    "chokudai-time",
+];
+
+const USAGE: &str = "usage: maze [options] [board file, - for stdin]
+       maze check-baseline BASELINE [options]
+  -a, --algorithm NAME  random, greedy, montecarlo, mcts, astar, beam, beam-time, parallel-beam,
+                        parallel-beam-time, tree-beam, chokudai, chokudai-time or all (default chokudai-time),
+                        repeatable or comma separated
+  -w, --width N         beam width (default 5 for beams, 1 for chokudai)
+  -d, --depth N         beam depth (default 3, the rest of the game for chokudai-time)
+  -b, --beam-number N   chokudai sweeps per turn (default 1)
+  -t, --time MS         time per turn of the time-budgeted algorithms (default 10)
+                        width, depth, beam number and time also take comma-separated lists, every
+                        combination is played on the same seeds and ranked at the end
+      --nodes N         expanded states per turn of the time-budgeted algorithms instead of the time
+      --iterations N    search iterations per turn of the time-budgeted algorithms instead of the time,
+                        with --nodes the search stops at whichever runs out first; both are reproducible
+      --dedup           drop states reached again at the same depth in the beam and chokudai searches
+                        (default off)
+      --max-per-region N
+                        states kept per region at each depth of beam, beam-time, parallel-beam-time and
+                        tree-beam, so that they don't crowd into one part of the board (default no cap)
+      --region-size N   side of the square regions in cells (default 1, every cell on its own)
+      --threads N       worker threads of parallel-beam-time (default all cores)
+  -j, --jobs N          games played at once on separate threads (default 1, keep it low for time-budgeted
+                        algorithms so their searches get a whole core each)
+  -e, --evaluator NAME  state, raw, nearest or decayed (default state)
+      --replan N        actions played from each plan before searching again (default 1)
+  -n, --games N         number of games (default 100)
+      --height N        rows of the generated boards (default 30)
+      --board-width N   columns of the generated boards (default 30)
+      --turns N         turns per game, also of a loaded board (default 100)
+      --walls           generate mazes with walls, the nearest evaluator then measures BFS distance
+  -s, --seed N          seed of the first game (default 0)
+  -f, --format FORMAT   text, csv or json (default text)
+      --export-csv PATH one row per game: seed, algorithm, parameters, score, elapsed time, nodes expanded
+      --export-json PATH
+                        the same rows as a JSON array
+      --tolerance X     score points the mean may drop below the baseline in check-baseline (default 0)
+      --gap             also report the optimality gap on 5x5 boards of 10 turns (text only)
+      --compare         compare every pair of algorithms game by game on the same seeds (text only)
+  -h, --help            print this message";
+
+// evaluator picked on the command line
+#[derive(Debug, Clone, Copy)]
+enum EvaluatorChoice {
+    State,
+    Raw,
+    NearestPoint,
+    Decayed(DecayedPoints),
+}
+
+impl EvaluatorChoice {
+    fn name(&self) -> &'static str {
+        match self {
+            EvaluatorChoice::State => "state",
+            EvaluatorChoice::Raw => "raw",
+            EvaluatorChoice::NearestPoint => "nearest",
+            EvaluatorChoice::Decayed(_) => "decayed",
+        }
+    }
+}
+
+impl<S: MazeGame> Evaluator<S> for EvaluatorChoice
+where
+    NearestPointDistance: Evaluator<S>,
+{
+    fn evaluate(&self, state: &mut S) {
+        match self {
+            EvaluatorChoice::State => StateEvaluation.evaluate(state),
+            EvaluatorChoice::Raw => RawScore.evaluate(state),
+            EvaluatorChoice::NearestPoint => NearestPointDistance.evaluate(state),
+            EvaluatorChoice::Decayed(evaluator) => evaluator.evaluate(state),
+        }
+    }
+}
+
+// search parameters given as comma-separated lists, every combination of them is played
+#[derive(Debug, Clone, Default)]
+struct SweepGrid {
+    beam_widths: Vec<usize>,
+    beam_depths: Vec<usize>,
+    beam_numbers: Vec<usize>,
+    time_thresholds: Vec<f64>, // ms
+}
+
+#[derive(Debug, Clone)]
+struct CliOptions {
+    algorithms: Vec<&'static str>,
+    // None picks the default of each algorithm
+    beam_width: Option<usize>,
+    beam_depth: Option<usize>,
+    beam_number: usize,
+    time_threshold: f64, // ms
+    // lists of more than one value for the four settings above
+    sweep: SweepGrid,
+    // limits of the budgeted algorithms instead of time_threshold
+    nodes: Option<usize>,
+    iterations: Option<usize>,
+    // duplicate pruning and diversity cap of the beam-style searches, see BeamOptions
+    dedup: bool,
+    max_per_region: Option<usize>,
+    region_size: usize,
+    threads: usize,
+    // games played at once
+    jobs: usize,
+    evaluator: EvaluatorChoice,
+    replan_interval: usize,
+    game_number: u64,
+    // board size of the generated games, a loaded board only takes end_turn from it
+    config: MazeConfig,
+    // generate WallMazeState boards instead of open ones
+    walls: bool,
+    seed: u64,
+    format: OutputFormat,
+    optimality_gap: bool,
+    compare: bool,
+    board: Option<String>,
+    export_csv: Option<String>,
+    export_json: Option<String>,
+    // games exported by --export-csv that check-baseline compares against
+    baseline: Option<String>,
+    tolerance: f64,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        CliOptions {
+            algorithms: vec!["chokudai-time"],
+            beam_width: None,
+            beam_depth: None,
+            beam_number: 1,
+            time_threshold: 10.0,
+            sweep: SweepGrid::default(),
+            nodes: None,
+            iterations: None,
+            dedup: false,
+            max_per_region: None,
+            region_size: 1,
+            threads: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            jobs: 1,
+            evaluator: EvaluatorChoice::State,
+            replan_interval: 1,
+            game_number: 100,
+            config: MazeConfig::default(),
+            walls: false,
+            seed: 0,
+            format: OutputFormat::Text,
+            optimality_gap: false,
+            compare: false,
+            board: None,
+            export_csv: None,
+            export_json: None,
+            baseline: None,
+            tolerance: 0.0,
+        }
+    }
+}
+
+impl CliOptions {
+    // None when help was asked for
+    fn parse<I: Iterator<Item = String>>(args: I) -> Result<Option<Self>, String> {
+        fn value<T: std::str::FromStr, I: Iterator<Item = String>>(flag: &str, args: &mut I) -> Result<T, String> {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", flag))?;
+            value
+                .parse()
+                .map_err(|_| format!("invalid value for {}: {}", flag, value))
+        }
+        fn values<T: std::str::FromStr, I: Iterator<Item = String>>(
+            flag: &str,
+            args: &mut I,
+        ) -> Result<Vec<T>, String> {
+            let list: String = value(flag, args)?;
+            list.split(',')
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid value for {}: {}", flag, value))
+                })
+                .collect()
+        }
+        let mut cli = CliOptions::default();
+        let mut algorithms = vec![];
+        let mut args = args.peekable();
+        if args.peek().map(|arg| arg.as_str()) == Some("check-baseline") {
+            let arg = args.next().unwrap();
+            cli.baseline = Some(value(&arg, &mut args)?);
+        }
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-a" | "--algorithm" => {
+                    let names: String = value(&arg, &mut args)?;
+                    for name in names.split(',') {
+                        if name == "all" {
+                            algorithms.extend_from_slice(&ALGORITHMS);
+                            continue;
+                        }
+                        let algorithm = ALGORITHMS
+                            .iter()
+                            .find(|&&algorithm| algorithm == name)
+                            .ok_or_else(|| format!("unknown algorithm: {}", name))?;
+                        algorithms.push(*algorithm);
+                    }
+                }
+                "-w" | "--width" => cli.sweep.beam_widths = values(&arg, &mut args)?,
+                "-d" | "--depth" => cli.sweep.beam_depths = values(&arg, &mut args)?,
+                "-b" | "--beam-number" => cli.sweep.beam_numbers = values(&arg, &mut args)?,
+                "-t" | "--time" => cli.sweep.time_thresholds = values(&arg, &mut args)?,
+                "--nodes" => cli.nodes = Some(value(&arg, &mut args)?),
+                "--iterations" => cli.iterations = Some(value(&arg, &mut args)?),
+                "--dedup" => cli.dedup = true,
+                "--max-per-region" => cli.max_per_region = Some(value(&arg, &mut args)?),
+                "--region-size" => cli.region_size = value(&arg, &mut args)?,
+                "--threads" => cli.threads = value(&arg, &mut args)?,
+                "-j" | "--jobs" => cli.jobs = value(&arg, &mut args)?,
+                "-e" | "--evaluator" => {
+                    let name: String = value(&arg, &mut args)?;
+                    cli.evaluator = match name.as_str() {
+                        "state" => EvaluatorChoice::State,
+                        "raw" => EvaluatorChoice::Raw,
+                        "nearest" => EvaluatorChoice::NearestPoint,
+                        "decayed" => EvaluatorChoice::Decayed(DecayedPoints {
+                            radius: 3,
+                            decay: 0.5,
+                        }),
+                        _ => return Err(format!("unknown evaluator: {}", name)),
+                    }
+                }
+                "--replan" => cli.replan_interval = value(&arg, &mut args)?,
+                "-n" | "--games" => cli.game_number = value(&arg, &mut args)?,
+                "--height" => cli.config.h = value(&arg, &mut args)?,
+                "--board-width" => cli.config.w = value(&arg, &mut args)?,
+                "--turns" => cli.config.end_turn = value(&arg, &mut args)?,
+                "--walls" => cli.walls = true,
+                "-s" | "--seed" => cli.seed = value(&arg, &mut args)?,
+                "-f" | "--format" => {
+                    let name: String = value(&arg, &mut args)?;
+                    cli.format = match name.as_str() {
+                        "text" => OutputFormat::Text,
+                        "csv" => OutputFormat::Csv,
+                        "json" => OutputFormat::Json,
+                        _ => return Err(format!("unknown format: {}", name)),
+                    }
+                }
+                "--gap" => cli.optimality_gap = true,
+                "--compare" => cli.compare = true,
+                "--export-csv" => cli.export_csv = Some(value(&arg, &mut args)?),
+                "--export-json" => cli.export_json = Some(value(&arg, &mut args)?),
+                "--tolerance" => cli.tolerance = value(&arg, &mut args)?,
+                _ if arg.starts_with('-') && arg != "-" => return Err(format!("unknown option: {}", arg)),
+                _ if cli.board.is_none() => cli.board = Some(arg),
+                _ => return Err(format!("unexpected argument: {}", arg)),
+            }
+        }
+        if !algorithms.is_empty() {
+            cli.algorithms = algorithms;
+        }
+        let sweep = &mut cli.sweep;
+        if cli.nodes == Some(0) || cli.iterations == Some(0) {
+            return Err("nodes and iterations must be positive".to_string());
+        }
+        if cli.max_per_region == Some(0) || cli.region_size == 0 {
+            return Err("max per region and region size must be positive".to_string());
+        }
+        if sweep.beam_widths.contains(&0) || sweep.beam_depths.contains(&0) || sweep.beam_numbers.contains(&0) {
+            return Err("width, depth and beam number must be positive".to_string());
+        }
+        if sweep
+            .time_thresholds
+            .iter()
+            .any(|time_threshold| time_threshold.is_nan() || *time_threshold <= 0.0)
+        {
+            return Err("time must be positive".to_string());
+        }
+        // a single value is a plain setting
+        if sweep.beam_widths.len() == 1 {
+            cli.beam_width = sweep.beam_widths.pop();
+        }
+        if sweep.beam_depths.len() == 1 {
+            cli.beam_depth = sweep.beam_depths.pop();
+        }
+        if sweep.beam_numbers.len() == 1 {
+            cli.beam_number = sweep.beam_numbers[0];
+            sweep.beam_numbers.clear();
+        }
+        if sweep.time_thresholds.len() == 1 {
+            cli.time_threshold = sweep.time_thresholds[0];
+            sweep.time_thresholds.clear();
+        }
+        if cli.replan_interval == 0 || cli.threads == 0 || cli.jobs == 0 || cli.game_number == 0 {
+            return Err("replan, threads, jobs and games must be positive".to_string());
+        }
+        if cli.config.h == 0 || cli.config.w == 0 || cli.config.end_turn == 0 {
+            return Err("height, board width and turns must be positive".to_string());
+        }
+        if cli.config.h * cli.config.w < 2 {
+            return Err("board needs at least two cells".to_string());
+        }
+        if cli.tolerance.is_nan() || cli.tolerance < 0.0 {
+            return Err("tolerance must not be negative".to_string());
+        }
+        if cli.optimality_gap && cli.format != OutputFormat::Text {
+            return Err("--gap only supports text output".to_string());
+        }
+        if cli.walls && cli.board.is_some() {
+            return Err("--walls only applies to generated boards".to_string());
+        }
+        if cli.compare && (cli.format != OutputFormat::Text || cli.board.is_some()) {
+            return Err("--compare needs text output and generated boards".to_string());
+        }
+        Ok(Some(cli))
+    }
+
+    // one copy of the options for every combination of the sweep lists, each with plain settings
+    fn configurations(&self) -> Vec<CliOptions> {
+        let mut configurations = vec![self.clone()];
+        let mut expand = |apply: &dyn Fn(&mut CliOptions, usize), values: usize| {
+            if values == 0 {
+                return;
+            }
+            configurations = configurations
+                .iter()
+                .flat_map(|configuration| {
+                    (0..values).map(move |i| {
+                        let mut configuration = configuration.clone();
+                        apply(&mut configuration, i);
+                        configuration
+                    })
+                })
+                .collect();
+        };
+        let sweep = &self.sweep;
+        expand(
+            &|cli, i| cli.beam_width = Some(sweep.beam_widths[i]),
+            sweep.beam_widths.len(),
+        );
+        expand(
+            &|cli, i| cli.beam_depth = Some(sweep.beam_depths[i]),
+            sweep.beam_depths.len(),
+        );
+        expand(
+            &|cli, i| cli.beam_number = sweep.beam_numbers[i],
+            sweep.beam_numbers.len(),
+        );
+        expand(
+            &|cli, i| cli.time_threshold = sweep.time_thresholds[i],
+            sweep.time_thresholds.len(),
+        );
+        for configuration in configurations.iter_mut() {
+            configuration.sweep = SweepGrid::default();
+        }
+        configurations
+    }
+
+    // the settings `name` is built with by makeAi, as space-separated key=value pairs
+    fn parameters(&self, name: &str) -> String {
+        let width = |default: usize| format!("width={}", self.beam_width.unwrap_or(default));
+        let depth = |default: &str| match self.beam_depth {
+            Some(beam_depth) => format!("depth={}", beam_depth),
+            None => format!("depth={}", default),
+        };
+        let budget = match (self.nodes, self.iterations) {
+            (None, None) => format!("time_ms={}", self.time_threshold),
+            (Some(nodes), None) => format!("nodes={}", nodes),
+            (None, Some(iterations)) => format!("iterations={}", iterations),
+            (Some(nodes), Some(iterations)) => format!("nodes={} iterations={}", nodes, iterations),
+        };
+        let evaluator = format!("evaluator={}", self.evaluator.name());
+        let mut parameters = match name {
+            "random" | "greedy" => vec![],
+            "montecarlo" | "mcts" | "astar" => vec![budget],
+            "beam" | "tree-beam" => vec![width(5), depth("3"), evaluator],
+            "beam-time" => vec![width(5), budget, evaluator],
+            "parallel-beam" => vec![
+                width(5),
+                depth("3"),
+                format!("threads={}", self.threads),
+                evaluator,
+            ],
+            "parallel-beam-time" => vec![
+                width(5),
+                budget,
+                format!("threads={}", self.threads),
+                evaluator,
+            ],
+            "chokudai" => vec![
+                width(1),
+                depth("3"),
+                format!("beam_number={}", self.beam_number),
+                evaluator,
+            ],
+            "chokudai-time" => vec![width(1), depth("rest"), budget, evaluator],
+            _ => unreachable!("unknown algorithm: {}", name),
+        };
+        let layer_beam = matches!(
+            name,
+            "beam" | "beam-time" | "parallel-beam" | "parallel-beam-time" | "tree-beam"
+        );
+        if layer_beam || name.starts_with("chokudai") {
+            parameters.push(format!("dedup={}", self.dedup));
+        }
+        if let (true, Some(max_per_region)) = (layer_beam, self.max_per_region) {
+            parameters.push(format!(
+                "max_per_region={} region_size={}",
+                max_per_region, self.region_size
+            ));
+        }
+        parameters.push(format!("replan={}", self.replan_interval));
+        // scores of different boards are not comparable
+        parameters.push(format!(
+            "board={}x{} turns={}",
+            self.config.h, self.config.w, self.config.end_turn
+        ));
+        if self.walls {
+            parameters.push("maze=walls".to_string());
+        }
+        parameters.join(" ")
+    }
+
+    // node and iteration budgets replace the time limit, they give the same result on every machine
+    fn budget(&self) -> SearchBudget {
+        if self.nodes.is_none() && self.iterations.is_none() {
+            return SearchBudget::time(self.time_threshold * 1e-3);
+        }
+        SearchBudget {
+            time_threshold: None,
+            nodes: self.nodes,
+            iterations: self.iterations,
+        }
+    }
+
+    // every algorithm under every configuration, once per distinct set of parameters
+    fn makeAis<S: MazeGame>(&self) -> Vec<((&'static str, Handler<S>), String)>
+    where
+        EvaluatorChoice: Evaluator<S>,
+    {
+        let mut ais: Vec<((&'static str, Handler<S>), String)> = vec![];
+        for configuration in self.configurations().iter() {
+            for name in configuration.algorithms.iter() {
+                let parameters = configuration.parameters(name);
+                if !ais
+                    .iter()
+                    .any(|(ai, other)| ai.0 == *name && *other == parameters)
+                {
+                    ais.push((configuration.makeAi(name), parameters));
+                }
+            }
+        }
+        ais
+    }
+
+    fn makeAi<S: MazeGame>(&self, name: &'static str) -> (&'static str, Handler<S>)
+    where
+        EvaluatorChoice: Evaluator<S>,
+    {
+        let budget = self.budget();
+        let options = BeamOptions {
+            dedup: self.dedup,
+            max_per_region: self.max_per_region,
+            region_size: self.region_size,
+        };
+        let evaluator = self.evaluator;
+        // with a node or iteration budget the parallel searches return the same plan for any thread count
+        let threads = self.threads;
+        let beam_width = self.beam_width.unwrap_or(5);
+        let beam_depth = self.beam_depth.unwrap_or(3);
+        let chokudai_width = self.beam_width.unwrap_or(1);
+        let chokudai_depth = self.beam_depth;
+        let beam_number = self.beam_number;
+        let handler: Handler<S> = match name {
+            "random" => Box::new(|state: &S, context: &mut GameContext| -> Vec<usize> {
+                vec![randomAction(state, &mut context.rng)]
+            }),
+            "greedy" => Box::new(|state: &S, _context: &mut GameContext| -> Vec<usize> { vec![greedyAction(state)] }),
+            "montecarlo" => Box::new(move |state: &S, context: &mut GameContext| -> Vec<usize> {
+                vec![primitiveMontecarloAction(
+                    state,
+                    budget,
+                    &mut context.rng,
+                    &mut context.stats,
+                )]
+            }),
+            // (state, budget, options, rng, stats)
+            "mcts" => Box::new(move |state: &S, context: &mut GameContext| -> Vec<usize> {
+                vec![mctsAction(
+                    state,
+                    budget,
+                    &MctsOptions::default(),
+                    &mut context.rng,
+                    &mut context.stats,
+                )]
+            }),
+            // (state, budget, stats)
+            "astar" => Box::new(move |state: &S, context: &mut GameContext| -> Vec<usize> {
+                aStarSearchPlan(state, budget, &mut context.stats).0
+            }),
+            // (state, beam_width, beam_depth, evaluator, options, stats)
+            "beam" => Box::new(move |state: &S, context: &mut GameContext| -> Vec<usize> {
+                beamSearchPlan(
+                    state,
+                    beam_width,
+                    beam_depth,
+                    &evaluator,
+                    &options,
+                    &mut context.stats,
+                )
+                .0
+            }),
+            // (state, beam_width, budget, evaluator, options, stats)
+            "beam-time" => Box::new(move |state: &S, context: &mut GameContext| -> Vec<usize> {
+                beamSearchPlanWithTimeThreshold(
+                    state,
+                    beam_width,
+                    budget,
+                    &evaluator,
+                    &options,
+                    &mut context.stats,
+                )
+                .0
+            }),
+            // (state, beam_width, beam_depth, threads, evaluator, options, stats)
+            "parallel-beam" => Box::new(move |state: &S, context: &mut GameContext| -> Vec<usize> {
+                parallelBeamSearchPlan(
+                    state,
+                    beam_width,
+                    beam_depth,
+                    threads,
+                    &evaluator,
+                    &options,
+                    &mut context.stats,
+                )
+                .0
+            }),
+            // (state, beam_width, budget, threads, evaluator, options, stats)
+            "parallel-beam-time" => Box::new(move |state: &S, context: &mut GameContext| -> Vec<usize> {
+                parallelBeamSearchPlanWithTimeThreshold(
+                    state,
+                    beam_width,
+                    budget,
+                    threads,
+                    &evaluator,
+                    &options,
+                    &mut context.stats,
+                )
+                .0
+            }),
+            // (state, beam_width, beam_depth, evaluator, options, stats)
+            "tree-beam" => Box::new(move |state: &S, context: &mut GameContext| -> Vec<usize> {
+                treeBeamSearchPlan(
+                    state,
+                    beam_width,
+                    beam_depth,
+                    &evaluator,
+                    &options,
+                    &mut context.stats,
+                )
+                .0
+            }),
+            // (state, beam_width, beam_depth, beam_number, evaluator, options, stats)
+            "chokudai" => Box::new(move |state: &S, context: &mut GameContext| -> Vec<usize> {
+                let beam_depth = chokudai_depth.unwrap_or(3);
+                chokudaiSearchPlan(
+                    state,
+                    chokudai_width,
+                    beam_depth,
+                    beam_number,
+                    &evaluator,
+                    &options,
+                    &mut context.stats,
+                )
+                .0
+            }),
+            // (state, beam_width, beam_depth, budget, evaluator, options, stats)
+            "chokudai-time" => Box::new(move |state: &S, context: &mut GameContext| -> Vec<usize> {
+                let beam_depth = chokudai_depth.unwrap_or(state.grid().config_.end_turn - state.grid().turn_);
+                chokudaiSearchPlanWithTimeThreshold(
+                    state,
+                    chokudai_width,
+                    beam_depth,
+                    budget,
+                    &evaluator,
+                    &options,
+                    &mut context.stats,
+                )
+                .0
+            }),
+            _ => unreachable!("unknown algorithm: {}", name),
+        };
+        (name, handler)
+    }
+}
+
+// a saved board is played once by every algorithm
+fn playBoard(cli: &CliOptions, state: MazeState) -> Vec<ScoreSummary> {
+    // the parameters name the size of the loaded board
+    let cli = &CliOptions {
+        config: state.grid_.config_,
+        ..cli.clone()
+    };
+    let mut summaries = vec![];
+    for (ai, parameters) in cli.makeAis::<MazeState>().iter() {
+        let start = Instant::now();
+        let (score, stats) = playState(ai, state.clone(), cli.seed, cli.replan_interval);
+        let elapsed = start.elapsed().as_secs_f64();
+        let summary = ScoreSummary {
+            name: ai.0.to_string(),
+            parameters: parameters.clone(),
+            seeds: vec![cli.seed],
+            scores: vec![score],
+            game_times: vec![elapsed],
+            game_nodes: vec![stats.nodes_expanded],
+            stats,
+            elapsed,
+        };
+        printSummary(&summary, cli.format);
+        summaries.push(summary);
+    }
+    summaries
+}
+
+// every algorithm on the boards of the seeds cli.seed.., generated as S
+fn playGenerated<S: MazeGame>(cli: &CliOptions) -> Vec<ScoreSummary>
+where
+    EvaluatorChoice: Evaluator<S>,
+{
+    let mut summaries = vec![];
+    let seeds = cli.seed..cli.seed + cli.game_number;
+    for (ai, parameters) in cli.makeAis::<S>().iter() {
+        let mut summary = testApiScore(
+            ai,
+            &cli.config,
+            seeds.clone(),
+            cli.replan_interval,
+            cli.jobs,
+        );
+        summary.parameters = parameters.clone();
+        printSummary(&summary, cli.format);
+        if cli.optimality_gap {
+            testOptimalityGap(
+                ai,
+                &MazeConfig::new(5, 5, 10, 0, 9),
+                seeds.clone(),
+                cli.replan_interval,
+                cli.jobs,
+            );
+        }
+        summaries.push(summary);
+    }
+    if cli.compare {
+        printComparisons(&summaries);
+    }
+    if cli.configurations().len() > 1 && cli.format == OutputFormat::Text {
+        printRanking(&summaries);
+    }
+    summaries
+}
+
+fn main() {
+    let cli = match CliOptions::parse(std::env::args().skip(1)) {
+        Ok(Some(cli)) => cli,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("maze: {}\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+    // read before playing, so that a bad path fails fast
+    let baseline = cli.baseline.as_ref().map(|path| {
+        readBaseline(path).unwrap_or_else(|err| {
+            eprintln!("maze: {}", err);
+            std::process::exit(2);
+        })
+    });
+    printSummaryHeader(cli.format);
+    let summaries = match &cli.board {
+        Some(path) => {
+            let state = MazeState::load(path, cli.config).unwrap_or_else(|err| {
+                eprintln!("{}: {}", path, err);
+                std::process::exit(1);
+            });
+            playBoard(&cli, state)
+        }
+        None if cli.walls => playGenerated::<WallMazeState>(&cli),
+        None => playGenerated::<MazeState>(&cli),
+    };
+
+    let exported = |path: &str, result: std::io::Result<()>| {
+        if let Err(err) = result {
+            eprintln!("{}: {}", path, err);
+            std::process::exit(2);
+        }
+    };
+    if let Some(path) = &cli.export_csv {
+        exported(path, writeGamesCsv(path, &summaries));
+    }
+    if let Some(path) = &cli.export_json {
+        exported(path, writeGamesJson(path, &summaries));
+    }
+    if let Some(baseline) = &baseline {
+        if !checkBaseline(&summaries, baseline, cli.tolerance) {
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mazeTextRoundTrip() {
+        for config in [
+            MazeConfig::default(),
+            MazeConfig::new(1, 7, 4, 1, 9),
+            MazeConfig::new(5, 3, 10, 0, 2),
+        ] {
+            for seed in 0..20 {
+                let mut state = MazeState::from_seed(config, seed);
+                for turn in 0..config.end_turn.min(seed as usize) {
+                    let actions = state.legalActions();
+                    state.advance(actions[turn % actions.len()]);
+                }
+                let parsed = MazeState::parse(&state.to_string(), config).unwrap();
+                // config, points, turn, character, score and hash
+                assert_eq!(state.grid_, parsed.grid_);
+            }
+        }
+    }
+
+    #[test]
+    fn wallMazeGamesStayOffWalls() {
+        for &(h, w) in &[
+            (9, 9),
+            (4, 4),
+            (4, 6),
+            (6, 4),
+            (6, 6),
+            (6, 7),
+            (1, 2),
+            (2, 2),
+            (3, 8),
+        ] {
+            let config = MazeConfig::new(h, w, 30, 0, 9);
+            for seed in 0..100 {
+                let mut state = WallMazeState::from_seed(config, seed);
+                while !state.isDone() {
+                    assert!(
+                        !state.legalActions().is_empty(),
+                        "{}x{} seed {} is walled in",
+                        h,
+                        w,
+                        seed
+                    );
+                    let action = beamSearchAction(
+                        &state,
+                        3,
+                        3,
+                        &StateEvaluation,
+                        &BeamOptions::default(),
+                        &mut SearchStats::default(),
+                    );
+                    state.advance(action);
+                    let character = state.grid_.character_;
+                    assert!(!state.walls_[character.y_ as usize * config.w + character.x_ as usize]);
+                }
+            }
+        }
+    }
+
+    // walks to the end of the game, checking the incremental hash on the way, then undoes every move
+    fn assertUndoRestores<S: MazeGame>(mut state: S, seed: u64) {
+        let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(seed);
+        let mut history = vec![];
+        while !state.isDone() {
+            let actions = state.legalActions();
+            let before = state.grid().clone();
+            let undo = state.advance(actions[rng.gen_range(0, actions.len())]);
+            let grid = state.grid();
+            let rehashed = MazeGrid::new(
+                grid.config_,
+                grid.points_.clone(),
+                grid.turn_,
+                grid.character_,
+                grid.game_score_,
+            );
+            assert_eq!(grid.hash_, rehashed.hash_);
+            history.push((before, undo));
+        }
+        while let Some((before, undo)) = history.pop() {
+            state.undo(undo);
+            assert_eq!(*state.grid(), before);
+        }
+    }
+
+    #[test]
+    fn undoRestoresStateAndHash() {
+        let config = MazeConfig::new(7, 9, 40, 0, 3);
+        for seed in 0..20 {
+            assertUndoRestores(MazeState::from_seed(config, seed), seed);
+            assertUndoRestores(WallMazeState::from_seed(config, seed), seed);
+        }
+    }
+
+    #[test]
+    fn parallelBeamMatchesBeamSearchForEveryThreadCount() {
+        let config = MazeConfig::new(8, 8, 20, 0, 9);
+        for seed in 0..5 {
+            let state = MazeState::from_seed(config, seed);
+            for options in [
+                BeamOptions::default(),
+                BeamOptions {
+                    dedup: true,
+                    max_per_region: Some(2),
+                    region_size: 3,
+                },
+            ] {
+                let mut stats = SearchStats::default();
+                let serial = beamSearchPlan(&state, 6, 5, &StateEvaluation, &options, &mut stats);
+                let serial_budget = beamSearchPlanWithTimeThreshold(
+                    &state,
+                    6,
+                    SearchBudget::nodes(200),
+                    &StateEvaluation,
+                    &options,
+                    &mut stats,
+                );
+                for threads in 1..=4 {
+                    let parallel = parallelBeamSearchPlan(
+                        &state,
+                        6,
+                        5,
+                        threads,
+                        &StateEvaluation,
+                        &options,
+                        &mut stats,
+                    );
+                    assert_eq!(serial, parallel, "seed {} threads {}", seed, threads);
+                    let parallel_budget = parallelBeamSearchPlanWithTimeThreshold(
+                        &state,
+                        6,
+                        SearchBudget::nodes(200),
+                        threads,
+                        &StateEvaluation,
+                        &options,
+                        &mut stats,
+                    );
+                    assert_eq!(
+                        serial_budget, parallel_budget,
+                        "seed {} threads {}",
+                        seed, threads
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parallelBeamOutOfTimeStillMoves() {
+        let state = MazeState::from_seed(MazeConfig::default(), 0);
+        for threads in 1..=4 {
+            let (plan, _) = parallelBeamSearchPlanWithTimeThreshold(
+                &state,
+                5,
+                SearchBudget::time(1e-9),
+                threads,
+                &StateEvaluation,
+                &BeamOptions::default(),
+                &mut SearchStats::default(),
+            );
+            assert!(!plan.is_empty());
+        }
+    }
+
+    // (state, beam_width, beam_depth, evaluator, options, stats)
+    type PlanSearch =
+        fn(&MazeState, usize, usize, &StateEvaluation, &BeamOptions, &mut SearchStats) -> (Vec<usize>, usize);
+
+    #[test]
+    fn treeBeamMatchesBeamSearch() {
+        let config = MazeConfig::new(8, 8, 20, 0, 9);
+        for seed in 0..10 {
+            let state = MazeState::from_seed(config, seed);
+            for beam_width in [1, 3, 7] {
+                for beam_depth in [1, 4, 25] {
+                    for (dedup, max_per_region, region_size) in [
+                        (false, None, 1),
+                        (true, None, 1),
+                        (false, Some(1), 1),
+                        (true, Some(2), 3),
+                    ] {
+                        let options = BeamOptions {
+                            dedup,
+                            max_per_region,
+                            region_size,
+                        };
+                        let run = |search: PlanSearch| {
+                            let mut stats = SearchStats::default();
+                            let plan = search(
+                                &state,
+                                beam_width,
+                                beam_depth,
+                                &StateEvaluation,
+                                &options,
+                                &mut stats,
+                            );
+                            (
+                                plan,
+                                stats.nodes_expanded,
+                                stats.duplicates_pruned,
+                                stats.diversity_pruned,
+                            )
+                        };
+                        assert_eq!(
+                            run(beamSearchPlan),
+                            run(treeBeamSearchPlan),
+                            "seed {} width {} depth {} options {:?}",
+                            seed,
+                            beam_width,
+                            beam_depth,
+                            options
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn baselineMatchesParameters() {
+        let summary = |parameters: &str, scores: Vec<usize>| ScoreSummary {
+            name: "beam".to_string(),
+            parameters: parameters.to_string(),
+            seeds: vec![0, 1],
+            game_times: vec![0.0; scores.len()],
+            game_nodes: vec![0; scores.len()],
+            scores,
+            stats: SearchStats::default(),
+            elapsed: 0.0,
+        };
+        let baseline: Vec<BaselineGame> = [("width=1", 10.0), ("width=8", 20.0)]
+            .iter()
+            .flat_map(|&(parameters, score)| {
+                (0..2).map(move |seed| BaselineGame {
+                    seed,
+                    algorithm: "beam".to_string(),
+                    parameters: parameters.to_string(),
+                    score,
+                })
+            })
+            .collect();
+        assert!(checkBaseline(
+            &[
+                summary("width=1", vec![10, 10]),
+                summary("width=8", vec![20, 20])
+            ],
+            &baseline,
+            0.0
+        ));
+        // width=8 regressed below its own baseline, though not below the width=1 one
+        assert!(!checkBaseline(
+            &[summary("width=8", vec![15, 15])],
+            &baseline,
+            0.0
+        ));
+        assert!(!checkBaseline(
+            &[summary("width=3", vec![20, 20])],
+            &baseline,
+            0.0
+        ));
+        let small_board = CliOptions {
+            config: MazeConfig::new(10, 10, 20, 0, 9),
+            ..CliOptions::default()
+        };
+        assert_ne!(
+            small_board.parameters("beam"),
+            CliOptions::default().parameters("beam")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "single digit")]
+    fn mazeConfigRejectsMultiDigitPoints() {
+        MazeConfig::new(3, 3, 5, 0, 10);
+    }
+
+    fn replayScore<S: MazeGame>(mut state: S, plan: &[usize]) -> usize {
+        for &action in plan {
+            assert!(state.legalActions().contains(&action));
+            state.advance(action);
+        }
+        assert!(state.isDone());
+        state.gameScore()
+    }
+
+    fn assertAStarMatchesExact<S: MazeGame>(config: MazeConfig) {
+        for seed in 0..10 {
+            let state = S::fromSeed(config, seed);
+            let (exact_plan, exact_score) = exactSolvePlan(&state, &mut SearchStats::default());
+            assert_eq!(replayScore(state.clone(), &exact_plan), exact_score);
+            let (astar_plan, astar_score) = aStarSearchPlan(
+                &state,
+                SearchBudget::nodes(usize::MAX),
+                &mut SearchStats::default(),
+            );
+            assert_eq!(astar_score, exact_score, "seed {}", seed);
+            assert_eq!(replayScore(state, &astar_plan), astar_score);
+        }
+    }
+
+    #[test]
+    fn aStarWithoutBudgetMatchesExactSolver() {
+        let config = MazeConfig::new(5, 5, 10, 0, 9);
+        assertAStarMatchesExact::<MazeState>(config);
+        assertAStarMatchesExact::<WallMazeState>(config);
+    }
+
+    #[test]
+    fn mctsNodeBudgetEndsOnTheLastTurn() {
+        let config = MazeConfig::new(8, 8, 3, 0, 9);
+        for seed in 0..10 {
+            let mut state = MazeState::from_seed(config, seed);
+            while state.grid_.turn_ + 1 < config.end_turn {
+                state.advance(greedyAction(&state));
+            }
+            let mut rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(seed);
+            let mut stats = SearchStats::default();
+            let action = mctsAction(
+                &state,
+                SearchBudget::nodes(50),
+                &MctsOptions::default(),
+                &mut rng,
+                &mut stats,
+            );
+            assert!(state.legalActions().contains(&action));
+        }
+    }
+
+    #[test]
+    fn singleCellBoardsAreRejected() {
+        assert!(matches!(
+            MazeState::parse("turn: 0\nscore: 0\n@\n", MazeConfig::default()),
+            Err(MazeParseError::SingleCell)
+        ));
+        let args = ["--height", "1", "--board-width", "1"];
+        assert!(CliOptions::parse(args.iter().map(|arg| arg.to_string())).is_err());
+        assert!(std::panic::catch_unwind(|| MazeConfig::new(1, 1, 5, 0, 9)).is_err());
+    }
 }